@@ -0,0 +1,34 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use crate::data::{greek, pct, DirStat};
+
+const DAY_MS: u64 = 1000 * 3600 * 24;
+
+/// Fixed age classes by modification time, each holds what is younger than its limit in days.
+pub const AGE_CLASSES: [(&str, u64); 5] = [("<30d", 30), ("30-90d", 90), ("90-365d", 365), ("1-3y", 3 * 365), (">3y", u64::MAX)];
+
+pub const AGE_CLASS_CNT: usize = AGE_CLASSES.len();
+
+/// index into AGE_CLASSES for a mod time relative to now, both in ms since the epoch
+pub fn age_class(now_ms: u64, mod_time: u64) -> usize {
+    let days = now_ms.saturating_sub(mod_time) / DAY_MS;
+    AGE_CLASSES.iter().position(|(_, limit)| days < *limit).unwrap_or(AGE_CLASS_CNT - 1)
+}
+
+pub fn now_ms() -> u64 {
+    std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_millis() as u64
+}
+
+/// Top directories by recursive size with the share of their bytes in each age class.
+pub fn report(dtree: &HashMap<PathBuf, DirStat>, top_n: usize) {
+    let mut dirs = dtree.iter().collect::<Vec<_>>();
+    dirs.sort_by_key(|(_, s)| std::cmp::Reverse(s.recurse.size));
+
+    println!("\nTop directories by recursive size with age profile (share of bytes by mod time)");
+    let header = AGE_CLASSES.iter().map(|(n, _)| format!("{:>8}", n)).collect::<String>();
+    println!("{:9}{} directory", "size", header);
+    for (p, s) in dirs.iter().take(top_n) {
+        let split = s.age_bytes.iter().map(|b| format!("{:>7.1}%", pct(*b, s.recurse.size))).collect::<String>();
+        println!("{}{} {}", greek(s.recurse.size as f64), split, p.display());
+    }
+}
@@ -0,0 +1,201 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    data::{greek, pct, DirStat, FileInfo},
+    pattern::path_names,
+};
+
+type OwnerBytes = HashMap<String, u64>;
+
+/// Recursive bytes per owner for directories down to `depth`, set against each directory's own owner
+/// to find subtrees where most of the data belongs to someone else. A finding under a finding with the
+/// same directory owner and the same top owner is collapsed into it.
+pub struct OwnerAnomalies {
+    depth: usize,
+    dirs: HashMap<PathBuf, OwnerBytes>,
+}
+
+/// a directory whose owner holds less than half of the bytes under it
+struct Finding<'a> {
+    owner: &'a str,
+    own: u64,
+    total: u64,
+    top: Option<(&'a String, u64)>,
+}
+
+/// the home name of a /user/<name> directory
+fn home_of(path: &Path) -> Option<&str> {
+    match path_names(path)[..] {
+        ["user", name] => Some(name),
+        _ => None,
+    }
+}
+
+/// `owner` against the others in `owners`, Some when most bytes belong to someone else
+fn finding<'a>(owner: &'a str, owners: &'a OwnerBytes) -> Option<Finding<'a>> {
+    let total = owners.values().sum::<u64>();
+    let own = owners.get(owner).copied().unwrap_or(0);
+    if own * 2 < total {
+        let top = owners.iter().max_by_key(|(_, b)| **b).map(|(o, b)| (o, *b));
+        Some(Finding { owner, own, total, top })
+    } else {
+        None
+    }
+}
+
+fn print_finding(p: &Path, f: &Finding) {
+    let (top_owner, top_bytes) = f.top.map_or(("-", 0), |(o, b)| (o.as_str(), b));
+    println!(
+        "{} {:5.1}% {:>10} {:5.1}% {:>10} {}",
+        greek(f.total as f64),
+        pct(f.own, f.total),
+        f.owner,
+        pct(top_bytes, f.total),
+        top_owner,
+        p.display()
+    );
+}
+
+impl OwnerAnomalies {
+    pub fn new(depth: usize) -> Self {
+        OwnerAnomalies {
+            depth,
+            dirs: HashMap::new(),
+        }
+    }
+
+    pub fn record(&mut self, fi: &FileInfo) {
+        let names = path_names(&fi.path);
+        let in_user = names.first() == Some(&"user");
+        // the ancestors from the parent up with the number of names in each
+        for (a, n) in fi.path.ancestors().skip(1).zip((0..names.len()).rev()) {
+            // homes are always kept so they can be checked against their name
+            if n > self.depth && !(in_user && n == 2) {
+                continue;
+            }
+            let owners = match self.dirs.get_mut(a) {
+                Some(o) => o,
+                None => self.dirs.entry(a.to_path_buf()).or_default(),
+            };
+            match owners.get_mut(&fi.user) {
+                Some(b) => *b += fi.stat.size,
+                None => {
+                    owners.insert(fi.user.clone(), fi.stat.size);
+                }
+            }
+        }
+    }
+
+    /// subtrees below the root whose directory owner holds less than half of the bytes, largest foreign share first
+    fn subtrees<'a>(&'a self, dtree: &'a HashMap<PathBuf, DirStat>) -> Vec<(&'a PathBuf, Finding<'a>)> {
+        let anomalous = |p: &Path| -> Option<Finding<'a>> {
+            // the root holds everyone's data
+            p.parent()?;
+            let (p, owners) = self.dirs.get_key_value(p)?;
+            finding(dtree.get(p)?.meta.as_ref()?.owner.as_str(), owners)
+        };
+        let mut subtrees = self
+            .dirs
+            .keys()
+            .filter(|p| path_names(p).len() <= self.depth)
+            .filter_map(|p| anomalous(p).map(|f| (p, f)))
+            // the same owner with the same top owner one level up is the same finding
+            .filter(|(p, f)| {
+                p.parent()
+                    .and_then(anomalous)
+                    .is_none_or(|pf| pf.owner != f.owner || pf.top.map(|t| t.0) != f.top.map(|t| t.0))
+            })
+            .collect::<Vec<_>>();
+        subtrees.sort_by_key(|(_, f)| std::cmp::Reverse(f.total - f.own));
+        subtrees
+    }
+
+    pub fn report(&self, dtree: &HashMap<PathBuf, DirStat>, top_n: usize) {
+        let subtrees = self.subtrees(dtree);
+        println!("\nOwnership anomalies - {} subtrees where the directory owner holds less than half of the bytes", subtrees.len());
+        println!("{:>9} {:>6} {:>10} {:>6} {:>10} directory", "size", "owner", "dir owner", "top", "top owner");
+        for (p, f) in subtrees.iter().take(top_n) {
+            print_finding(p, f);
+        }
+
+        let mut homes = self
+            .dirs
+            .iter()
+            .filter_map(|(p, owners)| Some((p, finding(home_of(p)?, owners)?)))
+            .collect::<Vec<_>>();
+        homes.sort_by_key(|(_, f)| std::cmp::Reverse(f.total - f.own));
+        println!("\nHome directories where their user holds a minority of the bytes - {} homes", homes.len());
+        println!("{:>9} {:>6} {:>10} {:>6} {:>10} directory", "size", "user", "home of", "top", "top owner");
+        for (p, f) in homes.iter().take(top_n) {
+            print_finding(p, f);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{tests, DirMeta};
+
+    fn entry(kind: &str, path: &str, size: u64, user: &str) -> FileInfo {
+        tests::entry(kind, path, size, 1_600_000_000_000, user)
+    }
+
+    fn subtrees(listing: &[(&str, &str, u64, &str)]) -> Vec<String> {
+        let mut oa = OwnerAnomalies::new(3);
+        let mut dtree = HashMap::new();
+        for (kind, path, size, user) in listing {
+            let fi = entry(kind, path, *size, user);
+            if fi.is_dir() {
+                dtree.insert(fi.path.clone(), DirStat::new(DirMeta::of(&fi)));
+            } else {
+                oa.record(&fi);
+            }
+        }
+        oa.subtrees(&dtree).iter().map(|(p, _)| p.display().to_string()).collect()
+    }
+
+    #[test]
+    fn homes_kept_below_the_depth() {
+        let mut oa = OwnerAnomalies::new(1);
+        oa.record(&entry("F", "/user/alice/x/f", 10, "bob"));
+        oa.record(&entry("F", "/user/alice/x/g", 5, "bob"));
+        oa.record(&entry("F", "/data/user/x/f", 7, "bob"));
+        let mut kept = oa.dirs.keys().map(|p| p.display().to_string()).collect::<Vec<_>>();
+        kept.sort();
+        assert_eq!(kept, ["/", "/data", "/user", "/user/alice"]);
+        assert_eq!(oa.dirs[Path::new("/user/alice")]["bob"], 15);
+    }
+
+    #[test]
+    fn root_is_never_a_finding() {
+        let found = subtrees(&[("D", "/", 0, "hdfs"), ("D", "/data", 0, "etl"), ("F", "/data/f", 10, "etl")]);
+        assert!(found.is_empty());
+    }
+
+    #[test]
+    fn collapse_needs_the_same_top_owner() {
+        let listing = [
+            ("D", "/", 0, "hdfs"),
+            ("D", "/data", 0, "hdfs"),
+            ("D", "/data/a", 0, "hdfs"),
+            ("D", "/data/b", 0, "hdfs"),
+            ("F", "/data/a/f", 10, "etl"),
+            ("F", "/data/a/g", 1, "hdfs"),
+            ("F", "/data/b/f", 30, "bi"),
+        ];
+        // /data/a is mostly etl's while /data as a whole is mostly bi's
+        assert_eq!(subtrees(&listing), ["/data", "/data/a"]);
+        // half is not most
+        assert!(subtrees(&[("D", "/data", 0, "hdfs"), ("F", "/data/f", 10, "etl"), ("F", "/data/g", 10, "hdfs")]).is_empty());
+    }
+
+    #[test]
+    fn same_owner_and_top_owner_collapse() {
+        let listing = [("D", "/data", 0, "hdfs"), ("D", "/data/a", 0, "hdfs"), ("F", "/data/a/f", 10, "etl")];
+        assert_eq!(subtrees(&listing), ["/data"]);
+    }
+}
@@ -0,0 +1,218 @@
+use crate::cli::TimeBucket;
+
+pub const DAY_MS: i64 = 1000 * 3600 * 24;
+
+/// days since 1970-01-01 for a proleptic gregorian date
+pub fn days_from_civil(y: i64, m: u32, d: u32) -> i64 {
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let m = m as i64;
+    let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + d as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+/// (year, month, day) for days since 1970-01-01
+pub fn civil_from_days(z: i64) -> (i64, u32, u32) {
+    let z = z + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let y = yoe + era * 400 + if m <= 2 { 1 } else { 0 };
+    (y, m, d)
+}
+
+fn add_months(y: i64, m: u32, months: u32) -> (i64, u32) {
+    let m0 = m - 1 + months;
+    (y + (m0 / 12) as i64, m0 % 12 + 1)
+}
+
+/// Calendar aligned time buckets in a fixed offset time zone.
+/// Bucket starts are kept as ms since the epoch so they compare with mod times directly.
+#[derive(Debug, Clone, Copy)]
+pub struct Calendar {
+    pub bucket: TimeBucket,
+    offset_ms: i64,
+}
+
+impl Calendar {
+    pub fn new(bucket: TimeBucket, offset_mins: i64) -> Self {
+        Calendar {
+            bucket,
+            offset_ms: offset_mins * 60 * 1000,
+        }
+    }
+
+    /// local days since the epoch for a time in ms
+    pub fn local_days(&self, ms: u64) -> i64 {
+        (ms as i64 + self.offset_ms).div_euclid(DAY_MS)
+    }
+
+    /// ms since the epoch of local midnight starting `days`
+    pub fn days_to_ms(&self, days: i64) -> u64 {
+        (days * DAY_MS - self.offset_ms).max(0) as u64
+    }
+
+    fn start_days(&self, days: i64) -> i64 {
+        let (y, m, _) = civil_from_days(days);
+        match self.bucket {
+            TimeBucket::Day => days,
+            // 1970-01-01 was a thursday, ISO weeks start on monday
+            TimeBucket::Week => days - (days + 3).rem_euclid(7),
+            TimeBucket::Month => days_from_civil(y, m, 1),
+            TimeBucket::Quarter => days_from_civil(y, (m - 1) / 3 * 3 + 1, 1),
+            TimeBucket::Year => days_from_civil(y, 1, 1),
+        }
+    }
+
+    fn end_days(&self, start: i64) -> i64 {
+        let (y, m, _) = civil_from_days(start);
+        let months = match self.bucket {
+            TimeBucket::Day => return start + 1,
+            TimeBucket::Week => return start + 7,
+            TimeBucket::Month => 1,
+            TimeBucket::Quarter => 3,
+            TimeBucket::Year => 12,
+        };
+        let (y, m) = add_months(y, m, months);
+        days_from_civil(y, m, 1)
+    }
+
+    /// start of the bucket holding `ms`
+    pub fn bucket_start(&self, ms: u64) -> u64 {
+        self.days_to_ms(self.start_days(self.local_days(ms)))
+    }
+
+    /// local days of the bucket starting at `start`, a start clamped to the epoch still
+    /// gets the days of the bucket it was clamped from
+    fn bucket_days(&self, start: u64) -> i64 {
+        self.start_days(self.local_days(start))
+    }
+
+    /// start of the bucket after the one starting at `start`
+    pub fn bucket_end(&self, start: u64) -> u64 {
+        self.days_to_ms(self.end_days(self.bucket_days(start)))
+    }
+
+    pub fn fmt_date(&self, ms: u64) -> String {
+        fmt_days(self.local_days(ms))
+    }
+
+    /// short name of the bucket starting at `start` like 2021-W27, 2021-07 or 2021-Q3
+    pub fn bucket_label(&self, start: u64) -> String {
+        let days = self.bucket_days(start);
+        let (y, m, _) = civil_from_days(days);
+        match self.bucket {
+            TimeBucket::Day => fmt_days(days),
+            TimeBucket::Week => {
+                // the ISO week belongs to the year its thursday falls in
+                let (ty, _, _) = civil_from_days(days + 3);
+                format!("{}-W{:02}", ty, (days + 3 - days_from_civil(ty, 1, 1)) / 7 + 1)
+            }
+            TimeBucket::Month => format!("{:04}-{:02}", y, m),
+            TimeBucket::Quarter => format!("{}-Q{}", y, (m - 1) / 3 + 1),
+            TimeBucket::Year => format!("{}", y),
+        }
+    }
+
+    /// label and inclusive date range of the bucket starting at `start`
+    pub fn fmt_bucket(&self, start: u64) -> String {
+        if self.bucket == TimeBucket::Day {
+            return self.fmt_date(start);
+        }
+        let days = self.bucket_days(start);
+        format!("{} [{} .. {}]", self.bucket_label(start), fmt_days(days), fmt_days(self.end_days(days) - 1))
+    }
+}
+
+/// yyyy-mm-dd of days since 1970-01-01
+fn fmt_days(days: i64) -> String {
+    let (y, m, d) = civil_from_days(days);
+    format!("{:04}-{:02}-{:02}", y, m, d)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(y: i64, m: u32, d: u32, h: i64) -> u64 {
+        (days_from_civil(y, m, d) * DAY_MS + h * 3600 * 1000) as u64
+    }
+
+    #[test]
+    fn civil_round_trip() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2000, 3, 1), 11017);
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        for days in [-800, -1, 0, 59, 60, 11016, 18993, 20000] {
+            let (y, m, d) = civil_from_days(days);
+            assert_eq!(days_from_civil(y, m, d), days);
+        }
+    }
+
+    #[test]
+    fn iso_weeks() {
+        let cal = Calendar::new(TimeBucket::Week, 0);
+        // a sunday still in the last week of the year before
+        assert_eq!(cal.bucket_label(cal.bucket_start(ms(2021, 1, 3, 12))), "2020-W53");
+        // a monday already in week 1 of the next year
+        assert_eq!(cal.bucket_label(cal.bucket_start(ms(2024, 12, 30, 0))), "2025-W01");
+        assert_eq!(cal.fmt_bucket(cal.bucket_start(ms(2024, 12, 11, 8))), "2024-W50 [2024-12-09 .. 2024-12-15]");
+        assert_eq!(cal.bucket_end(cal.bucket_start(ms(2024, 12, 11, 8))), ms(2024, 12, 16, 0));
+    }
+
+    #[test]
+    fn quarters_and_years() {
+        let cal = Calendar::new(TimeBucket::Quarter, 0);
+        let start = cal.bucket_start(ms(2021, 8, 15, 0));
+        assert_eq!(start, ms(2021, 7, 1, 0));
+        assert_eq!(cal.bucket_end(start), ms(2021, 10, 1, 0));
+        assert_eq!(cal.fmt_bucket(start), "2021-Q3 [2021-07-01 .. 2021-09-30]");
+        let start = cal.bucket_start(ms(2021, 11, 30, 0));
+        assert_eq!(cal.bucket_end(start), ms(2022, 1, 1, 0));
+
+        let cal = Calendar::new(TimeBucket::Year, 0);
+        assert_eq!(cal.fmt_bucket(cal.bucket_start(ms(2020, 2, 29, 0))), "2020 [2020-01-01 .. 2020-12-31]");
+    }
+
+    #[test]
+    fn negative_offset() {
+        let cal = Calendar::new(TimeBucket::Month, -5 * 60);
+        // 03:00 UTC on the first is still the evening before at -05:00
+        let t = ms(2021, 7, 1, 3);
+        assert_eq!(cal.fmt_date(t), "2021-06-30");
+        let start = cal.bucket_start(t);
+        assert_eq!(start, ms(2021, 6, 1, 5));
+        assert_eq!(cal.bucket_label(start), "2021-06");
+
+        let cal = Calendar::new(TimeBucket::Day, 5 * 60 + 30);
+        assert_eq!(cal.fmt_date(ms(2021, 6, 30, 20)), "2021-07-01");
+    }
+
+    #[test]
+    fn local_midnight() {
+        // a listing date west of UTC stays on its own day and in its own week
+        let cal = Calendar::new(TimeBucket::Week, -5 * 60);
+        let t = cal.days_to_ms(days_from_civil(2026, 9, 14));
+        assert_eq!(t, ms(2026, 9, 14, 5));
+        assert_eq!(cal.fmt_date(t), "2026-09-14");
+        assert_eq!(cal.bucket_label(cal.bucket_start(t)), "2026-W38");
+    }
+
+    #[test]
+    fn buckets_before_the_epoch() {
+        // the week of 1970-01-01 starts on monday 1969-12-29, its start is clamped to 0
+        let cal = Calendar::new(TimeBucket::Week, 0);
+        assert_eq!(cal.bucket_start(0), 0);
+        assert_eq!(cal.fmt_bucket(cal.bucket_start(0)), "1970-W01 [1969-12-29 .. 1970-01-04]");
+        assert_eq!(cal.bucket_end(cal.bucket_start(0)), ms(1970, 1, 5, 0));
+
+        let cal = Calendar::new(TimeBucket::Month, -5 * 60);
+        assert_eq!(cal.fmt_bucket(cal.bucket_start(0)), "1969-12 [1969-12-01 .. 1969-12-31]");
+    }
+}
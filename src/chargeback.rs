@@ -0,0 +1,164 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use anyhow::{Context, Result};
+
+use crate::{
+    cli::CliCfg,
+    data::{greek, top_dir, FileInfo},
+};
+
+const TB: f64 = 1024.0 * 1024.0 * 1024.0 * 1024.0;
+
+#[derive(Default)]
+struct Charge {
+    files: u64,
+    bytes: u64,
+    cost: f64,
+}
+
+impl Charge {
+    fn add(&mut self, bytes: u64, cost: f64) {
+        self.files += 1;
+        self.bytes += bytes;
+        self.cost += cost;
+    }
+}
+
+/// Cost per user, group and top level directory for one billing period.
+/// Each file is costed as it is read so tier rates can follow its path.
+pub struct Chargeback {
+    period: String,
+    rate: f64,
+    tiers: Vec<(PathBuf, f64)>,
+    replication: f64,
+    csv: Option<PathBuf>,
+    total: Charge,
+    by_user: HashMap<String, Charge>,
+    by_group: HashMap<String, Charge>,
+    by_top_dir: HashMap<PathBuf, Charge>,
+}
+
+impl Chargeback {
+    pub fn from_cli(cli: &CliCfg) -> Option<Chargeback> {
+        let rate = cli.cost_per_tb_month?;
+        let mut tiers = cli.cost_tier.clone();
+        // longest prefix first so the first match is the most specific tier
+        tiers.sort_by_key(|(p, _)| std::cmp::Reverse(p.components().count()));
+        let period = match &cli.cost_period {
+            Some(p) => p.clone(),
+            None => humantime::format_rfc3339(SystemTime::now()).to_string()[..7].to_string(),
+        };
+        Some(Chargeback {
+            period,
+            rate,
+            tiers,
+            replication: cli.cost_replication,
+            csv: cli.cost_csv.clone(),
+            total: Charge::default(),
+            by_user: HashMap::new(),
+            by_group: HashMap::new(),
+            by_top_dir: HashMap::new(),
+        })
+    }
+
+    fn rate_for(&self, fi: &FileInfo) -> f64 {
+        self.tiers.iter().find(|(p, _)| fi.path.starts_with(p)).map_or(self.rate, |(_, r)| *r)
+    }
+
+    pub fn record(&mut self, fi: &FileInfo) {
+        let cost = fi.stat.size as f64 * self.replication / TB * self.rate_for(fi);
+        let group = fi.group.as_deref().unwrap_or("-");
+        let top = top_dir(&fi.path).unwrap_or(Path::new("/"));
+
+        self.total.add(fi.stat.size, cost);
+        let u = match self.by_user.get_mut(&fi.user) {
+            Some(c) => c,
+            None => self.by_user.entry(fi.user.clone()).or_default(),
+        };
+        u.add(fi.stat.size, cost);
+        let g = match self.by_group.get_mut(group) {
+            Some(c) => c,
+            None => self.by_group.entry(group.to_string()).or_default(),
+        };
+        g.add(fi.stat.size, cost);
+        let t = match self.by_top_dir.get_mut(top) {
+            Some(c) => c,
+            None => self.by_top_dir.entry(top.to_path_buf()).or_default(),
+        };
+        t.add(fi.stat.size, cost);
+    }
+
+    fn sorted<'a>(m: impl Iterator<Item = (String, &'a Charge)>) -> Vec<(String, &'a Charge)> {
+        let mut v = m.collect::<Vec<_>>();
+        v.sort_by(|a, b| b.1.cost.partial_cmp(&a.1.cost).unwrap());
+        v
+    }
+
+    fn categories(&self) -> Vec<(&'static str, Vec<(String, &Charge)>)> {
+        vec![
+            ("user", Chargeback::sorted(self.by_user.iter().map(|(n, c)| (n.clone(), c)))),
+            ("group", Chargeback::sorted(self.by_group.iter().map(|(n, c)| (n.clone(), c)))),
+            ("top_dir", Chargeback::sorted(self.by_top_dir.iter().map(|(p, c)| (p.to_string_lossy().to_string(), c)))),
+        ]
+    }
+
+    pub fn report(&self, top_n: usize) {
+        println!(
+            "\nChargeback for period {} at {} per TB month, replication factor {}",
+            self.period, self.rate, self.replication
+        );
+        for (prefix, rate) in &self.tiers {
+            println!("  tier {} at {} per TB month", prefix.display(), rate);
+        }
+        println!("{:>12.2} {} {:8} total", self.total.cost, greek(self.total.bytes as f64), self.total.files);
+
+        for (cat, v) in self.categories() {
+            println!("\nTop chargeback by {}", cat);
+            for (n, c) in v.iter().take(top_n) {
+                println!("{:>12.2} {} {:8} {}", c.cost, greek(c.bytes as f64), c.files, n);
+            }
+        }
+
+        if let Some(path) = &self.csv {
+            if let Err(e) = self.write_csv(path) {
+                eprintln!("unable to write chargeback csv {}: {:#}", path.display(), e);
+            }
+        }
+    }
+
+    fn write_csv(&self, path: &Path) -> Result<()> {
+        let mut w = csv::Writer::from_path(path).with_context(|| format!("cannot create {}", path.display()))?;
+        w.write_record(["period", "category", "name", "files", "bytes", "replication", "billed_tb", "rate_per_tb_month", "cost"])?;
+
+        let replication = self.replication.to_string();
+        w.write_record([&self.period, "rate", "default", "", "", &replication, "", &self.rate.to_string(), ""])?;
+        for (prefix, rate) in &self.tiers {
+            w.write_record([&self.period, "rate", &prefix.to_string_lossy(), "", "", &replication, "", &rate.to_string(), ""])?;
+        }
+
+        let total = ("total", vec![("all".to_string(), &self.total)]);
+        for (cat, v) in std::iter::once(total).chain(self.categories()) {
+            for (n, c) in v {
+                let billed_tb = c.bytes as f64 * self.replication / TB;
+                let rate = if billed_tb > 0.0 { c.cost / billed_tb } else { self.rate };
+                w.write_record([
+                    &self.period,
+                    cat,
+                    &n,
+                    &c.files.to_string(),
+                    &c.bytes.to_string(),
+                    &replication,
+                    &format!("{:.6}", billed_tb),
+                    &format!("{:.4}", rate),
+                    &format!("{:.2}", c.cost),
+                ])?;
+            }
+        }
+        w.flush()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,104 @@
+use std::{
+    cmp::{max, min},
+    collections::HashMap,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use anyhow::Result;
+
+use crate::{
+    data::{get_age, greek, FileInfo},
+    pattern::{read_rules, PathPattern},
+};
+
+/// common HDFS areas, tried after the rules file
+const DEFAULT_RULES: [(&str, &str); 5] = [
+    ("/user/*/.Trash/**", "trash"),
+    ("/user/**", "user-home"),
+    ("/tmp/**", "tmp"),
+    ("/app-logs/**", "app-logs"),
+    ("/apps/hive/warehouse/**", "hive-warehouse"),
+];
+
+struct Class {
+    files: u64,
+    bytes: u64,
+    old: u64,
+    new: u64,
+}
+
+impl Class {
+    fn add(&mut self, fi: &FileInfo) {
+        self.files += 1;
+        self.bytes += fi.stat.size;
+        self.old = min(self.old, fi.stat.mod_time);
+        self.new = max(self.new, fi.stat.mod_time);
+    }
+}
+
+/// Files mapped to named classes by `<path pattern> <class>` rules, the first matching rule wins.
+pub struct Classes {
+    rules: Vec<(PathPattern, String)>,
+    classes: HashMap<String, Class>,
+    /// unclassified bytes and files by the directory they sit in
+    unclassified: HashMap<PathBuf, (u64, u64)>,
+}
+
+impl Classes {
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        let mut rules = match path {
+            Some(p) => read_rules(p)?.into_iter().map(|(_, pat, class)| (pat, class)).collect(),
+            None => vec![],
+        };
+        for (pat, class) in DEFAULT_RULES {
+            rules.push((PathPattern::new(pat)?, class.to_string()));
+        }
+        Ok(Classes {
+            rules,
+            classes: HashMap::new(),
+            unclassified: HashMap::new(),
+        })
+    }
+
+    pub fn record(&mut self, fi: &FileInfo) {
+        match self.rules.iter().find(|(p, _)| p.matches(&fi.path)) {
+            Some((_, class)) => {
+                let c = match self.classes.get_mut(class) {
+                    Some(c) => c,
+                    None => self.classes.entry(class.clone()).or_insert(Class {
+                        files: 0,
+                        bytes: 0,
+                        old: u64::MAX,
+                        new: 0,
+                    }),
+                };
+                c.add(fi);
+            }
+            None => {
+                let dir = fi.path.parent().unwrap_or(&fi.path).to_path_buf();
+                let u = self.unclassified.entry(dir).or_insert((0, 0));
+                u.0 += fi.stat.size;
+                u.1 += 1;
+            }
+        }
+    }
+
+    pub fn report(&self, top_n: usize) {
+        let now = SystemTime::now();
+        let mut classes = self.classes.iter().collect::<Vec<_>>();
+        classes.sort_by_key(|(_, c)| std::cmp::Reverse(c.bytes));
+        println!("\nUsage by class");
+        for (name, c) in &classes {
+            println!("{} {:8} {}  age:[{}-{}]", greek(c.bytes as f64), c.files, name, get_age(now, c.old), get_age(now, c.new));
+        }
+
+        let (bytes, files) = self.unclassified.values().fold((0, 0), |t, u| (t.0 + u.0, t.1 + u.1));
+        let mut dirs = self.unclassified.iter().collect::<Vec<_>>();
+        dirs.sort_by_key(|(_, u)| std::cmp::Reverse(u.0));
+        println!("\nUnclassified - {} in {} files, top directories by bytes directly in them", greek(bytes as f64), files);
+        for (p, u) in dirs.iter().take(top_n) {
+            println!("{} {:8} {}", greek(u.0 as f64), u.1, p.display());
+        }
+    }
+}
@@ -0,0 +1,356 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet},
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Context, Result};
+
+use crate::{
+    cli::CliCfg,
+    data::{greek, FileInfo},
+    pattern::{path_names, PathPattern},
+};
+
+/// lines kept in memory before a sorted run is spilled to a temporary file
+const RUN_LINES: usize = 1 << 20;
+
+/// Lines sorted in runs spilled to temporary files and merged when written out,
+/// so a list of millions of paths never has to fit in memory.
+struct SortedLines {
+    run_lines: usize,
+    lines: Vec<String>,
+    runs: Vec<PathBuf>,
+    count: u64,
+}
+
+impl SortedLines {
+    fn new(run_lines: usize) -> Self {
+        SortedLines {
+            run_lines,
+            lines: vec![],
+            runs: vec![],
+            count: 0,
+        }
+    }
+
+    fn push(&mut self, line: String) -> Result<()> {
+        self.lines.push(line);
+        self.count += 1;
+        if self.lines.len() >= self.run_lines {
+            self.spill()?;
+        }
+        Ok(())
+    }
+
+    fn spill(&mut self) -> Result<()> {
+        self.lines.sort_unstable();
+        let path = std::env::temp_dir().join(format!("postdu2-cleanup-{}-{}", std::process::id(), self.runs.len()));
+        let mut w = BufWriter::new(File::create(&path).with_context(|| format!("cannot create cleanup run {}", path.display()))?);
+        self.runs.push(path);
+        for l in self.lines.drain(..) {
+            writeln!(w, "{}", l)?;
+        }
+        w.flush()?;
+        Ok(())
+    }
+
+    /// writes all lines in order, merging the spilled runs with what is still in memory
+    fn write_to(&mut self, w: &mut impl Write) -> Result<()> {
+        self.lines.sort_unstable();
+        let mut runs = vec![];
+        for p in &self.runs {
+            runs.push(BufReader::new(File::open(p).with_context(|| format!("cannot read cleanup run {}", p.display()))?).lines());
+        }
+        let mut memory = self.lines.drain(..);
+        // the lines in memory are read as the run after the spilled ones
+        let mut next = |i: usize| -> Result<Option<String>> {
+            match runs.get_mut(i) {
+                Some(r) => Ok(r.next().transpose()?),
+                None => Ok(memory.next()),
+            }
+        };
+        let mut heap = BinaryHeap::new();
+        for i in 0..=self.runs.len() {
+            if let Some(l) = next(i)? {
+                heap.push(Reverse((l, i)));
+            }
+        }
+        while let Some(Reverse((l, i))) = heap.pop() {
+            writeln!(w, "{}", l)?;
+            if let Some(l) = next(i)? {
+                heap.push(Reverse((l, i)));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for SortedLines {
+    fn drop(&mut self) {
+        for p in &self.runs {
+            let _ = std::fs::remove_file(p);
+        }
+    }
+}
+
+/// Builds a reviewable list of cleanup candidates from age, size, path and owner criteria.
+/// It only ever writes the list - deleting is left to whoever reviews it.
+pub struct Cleanup {
+    out: PathBuf,
+    criteria: Vec<String>,
+    cutoff: Option<u64>,
+    min_size: Option<u64>,
+    paths: Vec<PathPattern>,
+    users: Vec<String>,
+    /// bytes and count of the matching files
+    matched: (u64, u64),
+    /// entries of the list that are known to be final
+    listed: SortedLines,
+    /// per directory: entries under it, matching entries under it - directories included
+    dirs: Option<HashMap<PathBuf, (u64, u64)>>,
+    /// matching files by parent while that parent may still be listed in their place, with the
+    /// fewest path components a directory listed for them may have
+    pending: HashMap<PathBuf, Vec<(PathBuf, usize)>>,
+}
+
+/// `/` and top level directories are never listed in place of their files
+const MIN_DIR_DEPTH: usize = 2;
+
+impl Cleanup {
+    pub fn from_cli(cli: &CliCfg, now_ms: u64) -> Result<Option<Self>> {
+        let out = match &cli.cleanup_list {
+            Some(out) => out.clone(),
+            None => return Ok(None),
+        };
+        let mut criteria = vec![];
+        if let Some(a) = cli.cleanup_min_age {
+            criteria.push(format!("modified more than {} ago", humantime::format_duration(a)));
+        }
+        if let Some(s) = cli.cleanup_min_size {
+            criteria.push(format!("at least {} bytes", s));
+        }
+        let paths = cli.cleanup_path.iter().map(|p| PathPattern::new(p)).collect::<Result<Vec<_>>>()?;
+        if !paths.is_empty() {
+            criteria.push(format!("path matches one of {}", cli.cleanup_path.join(" ")));
+        }
+        if !cli.cleanup_user.is_empty() {
+            criteria.push(format!("owned by one of {}", cli.cleanup_user.join(" ")));
+        }
+        if criteria.is_empty() {
+            return Err(anyhow!("--cleanup-list needs at least one of --cleanup-min-age, --cleanup-min-size, --cleanup-path or --cleanup-user"));
+        }
+        Ok(Some(Cleanup {
+            out,
+            criteria,
+            cutoff: cli.cleanup_min_age.map(|a| now_ms.saturating_sub(a.as_millis() as u64)),
+            min_size: cli.cleanup_min_size,
+            paths,
+            users: cli.cleanup_user.clone(),
+            matched: (0, 0),
+            listed: SortedLines::new(RUN_LINES),
+            dirs: if cli.cleanup_dirs { Some(HashMap::new()) } else { None },
+            pending: HashMap::new(),
+        }))
+    }
+
+    fn is_candidate(&self, fi: &FileInfo) -> bool {
+        self.cutoff.is_none_or(|c| fi.stat.mod_time < c)
+            && self.min_size.is_none_or(|s| fi.stat.size >= s)
+            && (self.paths.is_empty() || self.paths.iter().any(|p| p.matches(&fi.path)))
+            && (self.users.is_empty() || self.users.contains(&fi.user))
+    }
+
+    /// called for directories as well so a directory only counts as fully matching
+    /// when its subdirectories, empty ones included, match too
+    pub fn record(&mut self, fi: &FileInfo) -> Result<()> {
+        let hit = self.is_candidate(fi);
+        let file = hit && !fi.is_dir();
+        if file {
+            self.matched.0 += fi.stat.size;
+            self.matched.1 += 1;
+        }
+        let dirs = match &mut self.dirs {
+            Some(dirs) => dirs,
+            None if file => return self.listed.push(fi.path.display().to_string()),
+            None => return Ok(()),
+        };
+        for a in fi.path.ancestors().skip(1) {
+            let d = match dirs.get_mut(a) {
+                Some(d) => d,
+                None => dirs.entry(a.to_path_buf()).or_insert((0, 0)),
+            };
+            d.0 += 1;
+            if hit {
+                d.1 += 1;
+            } else if let Some(waiting) = self.pending.remove(a) {
+                // `a` can no longer be listed, so the files waiting on it are listed as they are
+                for (p, _) in waiting {
+                    self.listed.push(p.display().to_string())?;
+                }
+            }
+        }
+        if !file {
+            return Ok(());
+        }
+        // a directory listed for the file stays below the literal part of the pattern it matched
+        let floor = self.paths.iter().find(|p| p.matches(&fi.path)).map_or(0, |p| p.literal_depth() + 1).max(MIN_DIR_DEPTH);
+        match fi.path.parent() {
+            Some(parent) if path_names(parent).len() >= floor && dirs.get(parent).is_some_and(|d| d.0 == d.1) => {
+                let waiting = match self.pending.get_mut(parent) {
+                    Some(w) => w,
+                    None => self.pending.entry(parent.to_path_buf()).or_default(),
+                };
+                waiting.push((fi.path.clone(), floor));
+                Ok(())
+            }
+            _ => self.listed.push(fi.path.display().to_string()),
+        }
+    }
+
+    /// the highest directory above `path` whose entries all match with at least `min_depth` components
+    fn full_dir<'a>(&self, path: &'a Path, min_depth: usize) -> Option<&'a Path> {
+        let dirs = self.dirs.as_ref()?;
+        path.ancestors()
+            .skip(1)
+            .take_while(|a| path_names(a).len() >= min_depth && dirs.get(*a).is_some_and(|d| d.0 == d.1))
+            .last()
+    }
+
+    /// lists the directories the files still waiting are collapsed into
+    fn collapse_pending(&mut self) -> Result<()> {
+        let mut full = HashSet::new();
+        for (p, floor) in self.pending.values().flatten() {
+            full.insert(self.full_dir(p, *floor).unwrap_or(p).display().to_string());
+        }
+        for d in full {
+            self.listed.push(d)?;
+        }
+        self.pending.clear();
+        Ok(())
+    }
+
+    pub fn write_list(&mut self) -> Result<()> {
+        self.collapse_pending()?;
+
+        let (bytes, files) = self.matched;
+        let mut w = BufWriter::new(File::create(&self.out).with_context(|| format!("cannot create cleanup list {}", self.out.display()))?);
+        writeln!(w, "# cleanup candidates - review before removing anything")?;
+        for c in &self.criteria {
+            writeln!(w, "# criteria: {}", c)?;
+        }
+        writeln!(w, "# total: {} bytes ({}) in {} files", bytes, greek(bytes as f64).trim_end(), files)?;
+        writeln!(w, "# entries: {}{}", self.listed.count, if self.dirs.is_some() { " - fully matching directories are listed once" } else { "" })?;
+        // sorted so the same listing always gives the same list
+        self.listed.write_to(&mut w)?;
+        w.flush()?;
+        println!(
+            "\nCleanup list {} - {} in {} files as {} entries",
+            self.out.display(),
+            greek(bytes as f64),
+            files,
+            self.listed.count
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use structopt::StructOpt;
+
+    use super::*;
+    use crate::data::tests::entry;
+
+    const NOW: u64 = 1_700_000_000_000;
+    const OLD: u64 = NOW - 100 * 24 * 3600 * 1000;
+
+    fn cleanup(args: &[&str], listing: &[(&str, &str, u64)]) -> Cleanup {
+        let mut argv = vec!["postdu2", "-n", "5", "--cleanup-list", "/dev/null"];
+        argv.extend(args);
+        let cli = CliCfg::from_iter_safe(argv).unwrap();
+        let mut c = Cleanup::from_cli(&cli, NOW).unwrap().unwrap();
+        for (kind, path, mtime) in listing {
+            c.record(&entry(kind, path, 10, *mtime, "bob")).unwrap();
+        }
+        c
+    }
+
+    fn entries(mut c: Cleanup) -> Vec<String> {
+        c.collapse_pending().unwrap();
+        let mut out = vec![];
+        c.listed.write_to(&mut out).unwrap();
+        String::from_utf8(out).unwrap().lines().map(|l| l.to_string()).collect()
+    }
+
+    #[test]
+    fn sorted_runs() {
+        let mut s = SortedLines::new(2);
+        for l in ["d", "b", "f", "a", "e", "c", "a"] {
+            s.push(l.to_string()).unwrap();
+        }
+        let runs = s.runs.clone();
+        assert_eq!(runs.len(), 3);
+        let mut out = vec![];
+        s.write_to(&mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "a\na\nb\nc\nd\ne\nf\n");
+        drop(s);
+        assert!(runs.iter().all(|p| !p.exists()));
+    }
+
+    #[test]
+    fn files_without_cleanup_dirs() {
+        let listing = [("D", "/tmp/a", OLD), ("F", "/tmp/a/f2", OLD), ("F", "/tmp/a/f1", OLD), ("F", "/tmp/b", NOW)];
+        let c = cleanup(&["--cleanup-min-age", "30days"], &listing);
+        assert!(c.dirs.is_none());
+        assert_eq!(c.matched, (20, 2));
+        assert_eq!(entries(c), ["/tmp/a/f1", "/tmp/a/f2"]);
+    }
+
+    #[test]
+    fn never_collapses_to_the_root_or_top_level() {
+        let listing = [("D", "/tmp", OLD), ("D", "/tmp/a", OLD), ("F", "/tmp/a/f1", OLD), ("F", "/tmp/a/f2", OLD), ("F", "/tmp/f3", OLD)];
+        let c = cleanup(&["--cleanup-dirs", "--cleanup-path", "/tmp/**"], &listing);
+        assert_eq!(entries(c), ["/tmp/a", "/tmp/f3"]);
+        let c = cleanup(&["--cleanup-dirs", "--cleanup-min-age", "30days"], &listing);
+        assert_eq!(entries(c), ["/tmp/a", "/tmp/f3"]);
+    }
+
+    #[test]
+    fn stays_below_the_literal_prefix() {
+        let listing = [
+            ("D", "/user/bob/tmp", OLD),
+            ("D", "/user/bob/tmp/x", OLD),
+            ("F", "/user/bob/tmp/x/f1", OLD),
+            ("F", "/user/bob/tmp/f2", OLD),
+        ];
+        let c = cleanup(&["--cleanup-dirs", "--cleanup-path", "/user/bob/tmp/**"], &listing);
+        assert_eq!(entries(c), ["/user/bob/tmp/f2", "/user/bob/tmp/x"]);
+    }
+
+    #[test]
+    fn directories_count_as_entries() {
+        // a recent empty subdirectory keeps /data/a from being fully matching
+        let listing = [("D", "/data/a", OLD), ("F", "/data/a/f1", OLD), ("F", "/data/a/f2", OLD), ("D", "/data/a/new", NOW)];
+        let c = cleanup(&["--cleanup-dirs", "--cleanup-min-age", "30days"], &listing);
+        assert_eq!(entries(c), ["/data/a/f1", "/data/a/f2"]);
+
+        let listing = [("D", "/data/a", OLD), ("F", "/data/a/f1", OLD), ("D", "/data/a/old", OLD)];
+        let c = cleanup(&["--cleanup-dirs", "--cleanup-min-age", "30days"], &listing);
+        assert_eq!(entries(c), ["/data/a"]);
+    }
+
+    #[test]
+    fn partial_directories_list_files() {
+        let listing = [("D", "/data/a", OLD), ("F", "/data/a/f1", OLD), ("F", "/data/a/f2", NOW)];
+        let c = cleanup(&["--cleanup-dirs", "--cleanup-min-age", "30days"], &listing);
+        assert_eq!(entries(c), ["/data/a/f1"]);
+        // the same whichever comes first
+        let listing = [("F", "/data/a/f2", NOW), ("F", "/data/a/f1", OLD), ("D", "/data/a", OLD)];
+        let c = cleanup(&["--cleanup-dirs", "--cleanup-min-age", "30days"], &listing);
+        assert!(c.pending.is_empty());
+        assert_eq!(entries(c), ["/data/a/f1"]);
+    }
+}
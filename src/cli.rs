@@ -1,8 +1,10 @@
-use std::path::PathBuf;
+use std::{path::PathBuf, str::FromStr, time::Duration};
 
 use structopt::StructOpt;
 use structopt::clap::AppSettings::*;
 
+use crate::calendar::{civil_from_days, days_from_civil};
+
 
 #[derive(StructOpt, Debug, Clone)]
 #[structopt(
@@ -12,8 +14,9 @@ global_settings(& [
 )]
 /// Read a | delimited file of hdfs files and summarize the space results
 /// format of input from must be pipe limited and contain these fields
-/// <filetype>|<path>|<size>|last modifification time
+/// <filetype>|<path>|<size>|last modifification time|<user>[|<group>[|<last access time>]]
 /// Note filetype must be a F (for File) or D (D for directory) or S (symbolic link)
+/// With --input-format oiv it reads the output of hdfs oiv -p Delimited -delimiter '|' instead
 pub struct CliCfg {
     #[structopt(short = "f", name = "file", parse(from_os_str))]
     /// input file
@@ -45,5 +48,431 @@ pub struct CliCfg {
 
     #[structopt(short = "z", name = "input_stdin_is_zstd")]
     /// ticker timer in seconds - 0 means none
-    pub stdin_zstd: bool
+    pub stdin_zstd: bool,
+
+    #[structopt(long = "input-format", name = "format", default_value("native"))]
+    /// native (see above) or oiv for hdfs oiv Delimited output - its times are read in the --tz-offset zone
+    pub input_format: InputFormat,
+
+    #[structopt(long = "cost-per-tb-month", name = "cost_per_tb_month")]
+    /// turns on the chargeback report - cost of storing one TB (1024^4 bytes) for a month
+    pub cost_per_tb_month: Option<f64>,
+
+    #[structopt(long = "cost-tier", name = "prefix=cost", parse(try_from_str = parse_cost_tier))]
+    /// cost per TB month for paths under a prefix, e.g. /archive=4.5 - longest prefix wins, can be repeated
+    pub cost_tier: Vec<(PathBuf, f64)>,
+
+    #[structopt(long = "cost-replication", name = "replication_factor", default_value("1"))]
+    /// multiplier applied to file sizes before costing, e.g. 3 to bill raw replicated space
+    pub cost_replication: f64,
+
+    #[structopt(long = "cost-period", name = "period")]
+    /// label of the billing period shown in the report and csv - defaults to the current month
+    pub cost_period: Option<String>,
+
+    #[structopt(long = "cost-csv", name = "cost_csv_file", parse(from_os_str))]
+    /// also write the chargeback report as csv to this file
+    pub cost_csv: Option<PathBuf>,
+
+    #[structopt(long = "xtab-depth", name = "xtab_depth")]
+    /// break down the directories at this depth (1 is /data) by owner - reports top owners per directory and top directories per owner
+    pub xtab_depth: Option<usize>,
+
+    #[structopt(long = "per-user-top", name = "per_user_top")]
+    /// keep this many of the largest files and directories for every user - reported for the top users
+    pub per_user_top: Option<usize>,
+
+    #[structopt(long = "owner-report-dir", name = "owner_report_dir", parse(from_os_str))]
+    /// write one cleanup report file per owner into this directory
+    pub owner_report_dir: Option<PathBuf>,
+
+    #[structopt(long = "owner-report-by", name = "owner_key", default_value("user"))]
+    /// owner used for the report files: user or group (team)
+    pub owner_report_by: OwnerKey,
+
+    #[structopt(long = "time-bucket", name = "bucket", default_value("week"))]
+    /// calendar aligned timeframe for grouping mod times: day, week (ISO, from monday), month, quarter or year
+    pub time_bucket: TimeBucket,
+
+    #[structopt(long = "tz-offset", name = "offset", default_value("+00:00"), allow_hyphen_values = true, parse(try_from_str = parse_tz_offset))]
+    /// fixed time zone offset from UTC used for bucket boundaries and dates, e.g. -05:00 or +0530
+    pub tz_offset: i64,
+
+    #[structopt(long = "size-hist")]
+    /// report a log2 file size histogram globally, per user and for the top directories
+    pub size_hist: bool,
+
+    #[structopt(long = "hist-depth", name = "hist_depth", default_value("2"))]
+    /// directories down to this depth get their own file size histogram
+    pub hist_depth: usize,
+
+    #[structopt(long = "block-size", name = "block_size", default_value("128MB"), parse(try_from_str = parse_nonzero_size))]
+    /// HDFS block size, files below it are called out as small - accepts KB, MB, GB, TB suffixes (1024 based)
+    pub block_size: u64,
+
+    #[structopt(long = "small-files")]
+    /// report the directories holding the most small files with an estimate of what compaction would leave
+    pub small_files: bool,
+
+    #[structopt(long = "small-file-size", name = "small_size", parse(try_from_str = parse_size))]
+    /// files below this size are small - defaults to the block size
+    pub small_file_size: Option<u64>,
+
+    #[structopt(long = "compact-target", name = "target_size", default_value("128MB"), parse(try_from_str = parse_nonzero_size))]
+    /// file size small files are assumed to be compacted into, raised to the small file size when below it
+    pub compact_target: u64,
+
+    #[structopt(long = "namenode")]
+    /// estimate NameNode objects and heap per top level directory, user and subtree
+    pub namenode: bool,
+
+    #[structopt(long = "nn-inode-bytes", name = "inode_bytes", default_value("150"))]
+    /// NameNode heap bytes per file or directory inode
+    pub nn_inode_bytes: u64,
+
+    #[structopt(long = "nn-block-bytes", name = "block_bytes", default_value("150"))]
+    /// NameNode heap bytes per block
+    pub nn_block_bytes: u64,
+
+    #[structopt(long = "stale-age", name = "stale_age", parse(try_from_str = humantime::parse_duration))]
+    /// report directories whose newest file is older than this, e.g. 1year or 180days
+    pub stale_age: Option<Duration>,
+
+    #[structopt(long = "recent-window", name = "recent_window", parse(try_from_str = humantime::parse_duration))]
+    /// report where files modified within this window landed, e.g. 7days
+    pub recent_window: Option<Duration>,
+
+    #[structopt(long = "age-profile")]
+    /// report the top directories with their recursive bytes split into age classes
+    pub age_profile: bool,
+
+    #[structopt(long = "cold-age", name = "cold_age", parse(try_from_str = humantime::parse_duration))]
+    /// report files not read for this long by directory and user - needs access times in the input
+    pub cold_age: Option<Duration>,
+
+    #[structopt(long = "retention-rules", name = "retention_file", parse(from_os_str))]
+    /// file of "<path pattern> <max age>" lines like "/data/logs/** 90days" - reports files kept past their rule
+    pub retention_rules: Option<PathBuf>,
+
+    #[structopt(long = "cleanup-list", name = "cleanup_file", parse(from_os_str))]
+    /// write the paths of files matching all the --cleanup-* criteria to this file for review - nothing is ever deleted
+    pub cleanup_list: Option<PathBuf>,
+
+    #[structopt(long = "cleanup-min-age", name = "cleanup_age", parse(try_from_str = humantime::parse_duration))]
+    /// cleanup candidates were modified longer ago than this
+    pub cleanup_min_age: Option<Duration>,
+
+    #[structopt(long = "cleanup-min-size", name = "cleanup_size", parse(try_from_str = parse_size))]
+    /// cleanup candidates are at least this big
+    pub cleanup_min_size: Option<u64>,
+
+    #[structopt(long = "cleanup-path", name = "cleanup_pattern")]
+    /// cleanup candidates match one of these path patterns like /tmp/** - can be repeated
+    pub cleanup_path: Vec<String>,
+
+    #[structopt(long = "cleanup-user", name = "cleanup_user")]
+    /// cleanup candidates are owned by one of these users - can be repeated
+    pub cleanup_user: Vec<String>,
+
+    #[structopt(long = "cleanup-dirs")]
+    /// list the highest directory whose entries all match instead of each of its files, never above a --cleanup-path prefix or at the top level
+    pub cleanup_dirs: bool,
+
+    #[structopt(long = "history", name = "history_file", parse(from_os_str))]
+    /// append this run's totals, user and directory sizes to this history file
+    pub history: Option<PathBuf>,
+
+    #[structopt(long = "history-depth", name = "history_depth", default_value("2"))]
+    /// directories down to this depth are recorded in the history
+    pub history_depth: usize,
+
+    #[structopt(long = "listing-date", name = "yyyy-mm-dd", parse(try_from_str = parse_date))]
+    /// date the listing was taken in the --tz-offset zone, recorded in the history - defaults to now
+    pub listing_date: Option<i64>,
+
+    #[structopt(long = "trend")]
+    /// report growth and week over week deltas from the --history file instead of reading a listing
+    pub trend: bool,
+
+    #[structopt(long = "forecast")]
+    /// fit growth rates per top level directory, user and overall and project when the --capacity fills up
+    pub forecast: bool,
+
+    #[structopt(long = "capacity", name = "capacity", parse(try_from_str = parse_size))]
+    /// usable cluster capacity the forecast projects against, e.g. 2PB
+    pub capacity: Option<u64>,
+
+    #[structopt(long = "fill-threshold", name = "percent", default_value("80"))]
+    /// percent of the capacity considered full
+    pub fill_threshold: f64,
+
+    #[structopt(long = "forecast-window", name = "forecast_window", default_value("90days"), parse(try_from_str = humantime::parse_duration))]
+    /// growth is fitted over this much of the history or of the mod times before now
+    pub forecast_window: Duration,
+
+    #[structopt(long = "partitions")]
+    /// roll hive style key=value partition directories up to their tables
+    pub partitions: bool,
+
+    #[structopt(long = "templates")]
+    /// group directories that differ only in dates, numbers or uuids into path templates like /logs/{yyyy}/{mm}/{dd}/host{n}
+    pub templates: bool,
+
+    #[structopt(long = "path-date", name = "date_pattern")]
+    /// path pattern locating the logical date in a path with {yyyy}, {mm} and {dd}, e.g. /**/dt={yyyy}-{mm}-{dd}/** - repeatable, first match wins
+    pub path_date: Vec<String>,
+
+    #[structopt(long = "path-date-age", name = "path_date_age", parse(try_from_str = humantime::parse_duration))]
+    /// report the dated data older than this by path date and by mod time as reclaimable
+    pub path_date_age: Option<Duration>,
+
+    #[structopt(long = "classify")]
+    /// report size, count and age per class using --class-rules and the built-in rules for /user, /tmp, /app-logs, the hive warehouse and trash
+    pub classify: bool,
+
+    #[structopt(long = "class-rules", name = "class_rules_file", parse(from_os_str))]
+    /// rules file of "<path pattern> <class>" lines tried before the built-in rules, implies --classify
+    pub class_rules: Option<PathBuf>,
+
+    #[structopt(long = "trash")]
+    /// report user trash bytes, Current apart from the checkpoints and what expires at the next checkpoint
+    pub trash: bool,
+
+    #[structopt(long = "trash-interval", name = "trash_interval", default_value("1day"), parse(try_from_str = humantime::parse_duration))]
+    /// the cluster's fs.trash.interval, checkpoints older than this go at the next checkpoint
+    pub trash_interval: Duration,
+
+    #[structopt(long = "snapshots", name = "snapshot_mode", default_value("include"))]
+    /// entries under .snapshot directories: include them like live data, exclude them or roll them up separately
+    pub snapshots: SnapshotMode,
+
+    #[structopt(long = "leftovers")]
+    /// report _temporary, .hive-staging and .spark-staging directories and part-* output without a _SUCCESS marker
+    pub leftovers: bool,
+
+    #[structopt(long = "dir-meta")]
+    /// report directories holding content of other owners and directories modified recently with only old content
+    pub dir_meta: bool,
+
+    #[structopt(long = "touched-window", name = "touched_window", default_value("7days"), parse(try_from_str = humantime::parse_duration))]
+    /// a directory modified within this window counts as recently modified
+    pub touched_window: Duration,
+
+    #[structopt(long = "old-content-age", name = "old_content_age", default_value("90days"), parse(try_from_str = humantime::parse_duration))]
+    /// content with nothing newer than this counts as old
+    pub old_content_age: Duration,
+
+    #[structopt(long = "owner-anomalies")]
+    /// report subtrees where most bytes belong to someone other than the directory owner and homes their user holds a minority of
+    pub owner_anomalies: bool,
+
+    #[structopt(long = "anomaly-depth", name = "anomaly_depth", default_value("3"))]
+    /// directories down to this depth are checked for ownership anomalies
+    pub anomaly_depth: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InputFormat {
+    Native,
+    Oiv,
+}
+
+impl FromStr for InputFormat {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "native" => Ok(InputFormat::Native),
+            "oiv" => Ok(InputFormat::Oiv),
+            _ => Err(format!("unknown input format \"{}\", expected native or oiv", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimeBucket {
+    Day,
+    Week,
+    Month,
+    Quarter,
+    Year,
+}
+
+impl FromStr for TimeBucket {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "day" => Ok(TimeBucket::Day),
+            "week" => Ok(TimeBucket::Week),
+            "month" => Ok(TimeBucket::Month),
+            "quarter" => Ok(TimeBucket::Quarter),
+            "year" => Ok(TimeBucket::Year),
+            _ => Err(format!("unknown time bucket \"{}\", expected day, week, month, quarter or year", s)),
+        }
+    }
+}
+
+/// bytes from a number with an optional 1024 based suffix like 128MB, 1.5T or 4k
+pub fn parse_size(s: &str) -> Result<u64, String> {
+    let t = s.trim();
+    let split = t.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(t.len());
+    let num = t[..split].parse::<f64>().map_err(|_| format!("size \"{}\" does not start with a number", s))?;
+    let multi: u64 = match t[split..].trim().to_ascii_uppercase().trim_end_matches(['B', 'I']) {
+        "" => 1,
+        "K" => 1 << 10,
+        "M" => 1 << 20,
+        "G" => 1 << 30,
+        "T" => 1 << 40,
+        "P" => 1 << 50,
+        "E" => 1 << 60,
+        _ => return Err(format!("size \"{}\" has an unknown unit", s)),
+    };
+    Ok((num * multi as f64) as u64)
+}
+
+/// parse_size rejecting 0, for sizes used as divisors
+fn parse_nonzero_size(s: &str) -> Result<u64, String> {
+    match parse_size(s)? {
+        0 => Err(format!("size \"{}\" must be above 0", s)),
+        n => Ok(n),
+    }
+}
+
+/// days since the epoch for yyyy-mm-dd, the time zone is applied once --tz-offset is known
+fn parse_date(s: &str) -> Result<i64, String> {
+    let bad = || format!("date \"{}\" is not of the form yyyy-mm-dd", s);
+    let f = s.split('-').map(|v| v.parse::<u32>().map_err(|_| bad())).collect::<Result<Vec<_>, _>>()?;
+    match f[..] {
+        [y, m, d] if (1..=12).contains(&m) && y >= 1970 => {
+            let days = days_from_civil(y as i64, m, d);
+            // a day past the end of the month comes back as a different date
+            if civil_from_days(days) == (y as i64, m, d) {
+                Ok(days)
+            } else {
+                Err(format!("date \"{}\" does not exist", s))
+            }
+        }
+        _ => Err(bad()),
+    }
+}
+
+/// offset in minutes from [+-]HH:MM, [+-]HHMM or [+-]HH, Z and UTC are zero
+fn parse_tz_offset(s: &str) -> Result<i64, String> {
+    if s == "Z" || s.eq_ignore_ascii_case("utc") {
+        return Ok(0);
+    }
+    let bad = || format!("time zone offset \"{}\" is not of the form +HH:MM", s);
+    let (sign, rest) = match s.chars().next() {
+        Some('+') => (1, &s[1..]),
+        Some('-') => (-1, &s[1..]),
+        _ => (1, s),
+    };
+    let digits = rest.replace(':', "");
+    if !(digits.len() == 2 || digits.len() == 4) || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return Err(bad());
+    }
+    let h = digits[..2].parse::<i64>().map_err(|_| bad())?;
+    let m = if digits.len() == 4 { digits[2..].parse::<i64>().map_err(|_| bad())? } else { 0 };
+    if h > 14 || m > 59 {
+        return Err(bad());
+    }
+    Ok(sign * (h * 60 + m))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OwnerKey {
+    User,
+    Group,
+}
+
+impl FromStr for OwnerKey {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "user" => Ok(OwnerKey::User),
+            "group" => Ok(OwnerKey::Group),
+            _ => Err(format!("unknown owner \"{}\", expected user or group", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SnapshotMode {
+    Include,
+    Exclude,
+    Separate,
+}
+
+impl FromStr for SnapshotMode {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "include" => Ok(SnapshotMode::Include),
+            "exclude" => Ok(SnapshotMode::Exclude),
+            "separate" => Ok(SnapshotMode::Separate),
+            _ => Err(format!("unknown snapshot mode \"{}\", expected include, exclude or separate", s)),
+        }
+    }
+}
+
+fn parse_cost_tier(s: &str) -> Result<(PathBuf, f64), String> {
+    let i = s.rfind('=').ok_or_else(|| format!("cost tier \"{}\" is not of the form <path prefix>=<cost>", s))?;
+    let cost = s[i + 1..].parse::<f64>().map_err(|e| format!("cost tier \"{}\" has a bad cost: {}", s, e))?;
+    Ok((PathBuf::from(&s[..i]), cost))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tz_offsets() {
+        assert_eq!(parse_tz_offset("+00:00"), Ok(0));
+        assert_eq!(parse_tz_offset("UTC"), Ok(0));
+        assert_eq!(parse_tz_offset("-05:00"), Ok(-300));
+        assert_eq!(parse_tz_offset("+0530"), Ok(330));
+        assert_eq!(parse_tz_offset("-03"), Ok(-180));
+        assert!(parse_tz_offset("+15:00").is_err());
+        assert!(parse_tz_offset("+05:60").is_err());
+        assert!(parse_tz_offset("-5").is_err());
+        assert!(parse_tz_offset("EST").is_err());
+    }
+
+    #[test]
+    fn tz_offset_arg() {
+        // zones west of UTC start with - and must not be taken for a flag
+        let cli = CliCfg::from_iter_safe(&["postdu2", "-n", "5", "--tz-offset", "-05:00"]).unwrap();
+        assert_eq!(cli.tz_offset, -300);
+        let cli = CliCfg::from_iter_safe(&["postdu2", "-n", "5", "--tz-offset=-0330"]).unwrap();
+        assert_eq!(cli.tz_offset, -210);
+        let cli = CliCfg::from_iter_safe(&["postdu2", "-n", "5", "--tz-offset", "+05:30"]).unwrap();
+        assert_eq!(cli.tz_offset, 330);
+    }
+
+    #[test]
+    fn dates() {
+        assert_eq!(parse_date("1970-01-02"), Ok(1));
+        assert_eq!(parse_date("2026-09-14"), Ok(days_from_civil(2026, 9, 14)));
+        assert_eq!(parse_date("2024-02-29"), Ok(days_from_civil(2024, 2, 29)));
+        assert!(parse_date("2026-02-29").is_err());
+        assert!(parse_date("2026-02-31").is_err());
+        assert!(parse_date("2026-04-31").is_err());
+        assert!(parse_date("2026-13-01").is_err());
+        assert!(parse_date("2026-09").is_err());
+        assert!(parse_date("1969-12-31").is_err());
+    }
+
+    #[test]
+    fn sizes() {
+        assert_eq!(parse_size("0"), Ok(0));
+        assert_eq!(parse_size("4096"), Ok(4096));
+        assert_eq!(parse_size("4k"), Ok(4096));
+        assert_eq!(parse_size("128MB"), Ok(128 << 20));
+        assert_eq!(parse_size("1.5T"), Ok(3 << 39));
+        assert_eq!(parse_size("2 GiB"), Ok(2 << 30));
+        assert_eq!(parse_size("1PB"), Ok(1 << 50));
+        assert!(parse_size("MB").is_err());
+        assert!(parse_size("12XB").is_err());
+        assert!(parse_nonzero_size("0MB").is_err());
+        assert_eq!(parse_nonzero_size("1k"), Ok(1024));
+    }
 }
@@ -0,0 +1,83 @@
+use std::{collections::HashMap, path::PathBuf, time::SystemTime};
+
+use crate::data::{get_age, greek, pct, DirStat, FileInfo};
+
+/// Files not read within a window, by their last access time.
+/// Directories are kept recursively so their cold share can be set against their whole size.
+pub struct Cold {
+    cutoff: u64,
+    total: (u64, u64),
+    no_access_time: u64,
+    dirs: HashMap<PathBuf, (u64, u64)>,
+    users: HashMap<String, (u64, u64)>,
+}
+
+impl Cold {
+    pub fn new(now_ms: u64, age_ms: u64) -> Self {
+        Cold {
+            cutoff: now_ms.saturating_sub(age_ms),
+            total: (0, 0),
+            no_access_time: 0,
+            dirs: HashMap::new(),
+            users: HashMap::new(),
+        }
+    }
+
+    pub fn record(&mut self, fi: &FileInfo) {
+        let access_time = match fi.stat.access_time {
+            Some(a) => a,
+            None => {
+                self.no_access_time += 1;
+                return;
+            }
+        };
+        if access_time >= self.cutoff {
+            return;
+        }
+        let size = fi.stat.size;
+        self.total.0 += size;
+        self.total.1 += 1;
+        for a in fi.path.ancestors().skip(1) {
+            let d = match self.dirs.get_mut(a) {
+                Some(d) => d,
+                None => self.dirs.entry(a.to_path_buf()).or_insert((0, 0)),
+            };
+            d.0 += size;
+            d.1 += 1;
+        }
+        let u = match self.users.get_mut(&fi.user) {
+            Some(u) => u,
+            None => self.users.entry(fi.user.clone()).or_insert((0, 0)),
+        };
+        u.0 += size;
+        u.1 += 1;
+    }
+
+    pub fn report(&self, dtree: &HashMap<PathBuf, DirStat>, top_n: usize) {
+        let now = SystemTime::now();
+        println!(
+            "\nCold data not read in {} - {} in {} files",
+            get_age(now, self.cutoff),
+            greek(self.total.0 as f64),
+            self.total.1
+        );
+        if self.no_access_time > 0 {
+            println!("{} files have no access time and are left out", self.no_access_time);
+        }
+
+        let mut dirs = self.dirs.iter().collect::<Vec<_>>();
+        dirs.sort_by_key(|(_, c)| std::cmp::Reverse(c.0));
+        println!("\nTop directories by cold bytes recursively in them (share of the directory)");
+        for (p, c) in dirs.iter().take(top_n) {
+            let size = dtree.get(*p).map_or(0, |s| s.recurse.size);
+            println!("{} {:8} {:5.1}% {}", greek(c.0 as f64), c.1, pct(c.0, size), p.display());
+        }
+
+        let mut users = self.users.iter().collect::<Vec<_>>();
+        users.sort_by_key(|(_, c)| std::cmp::Reverse(c.0));
+        println!("\nTop users by cold bytes");
+        for (u, c) in users.iter().take(top_n) {
+            println!("{} {:8} {}", greek(c.0 as f64), c.1, u);
+        }
+    }
+}
@@ -3,9 +3,9 @@
 
 use std::{
     cmp::{max, min},
-    collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap},
+    collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet},
     fmt::Display,
-    path::PathBuf,
+    path::{Component, Path, PathBuf},
     time::{Duration, SystemTime},
 };
 
@@ -14,7 +14,32 @@ use csv::{ByteRecord, StringRecord};
 use humantime::FormattedDuration;
 use url::Url;
 
-use crate::cli::CliCfg;
+use crate::age::{age_class, now_ms, AGE_CLASS_CNT};
+use crate::age;
+use crate::anomaly::OwnerAnomalies;
+use crate::calendar::{days_from_civil, Calendar};
+use crate::chargeback::Chargeback;
+use crate::classify::Classes;
+use crate::cleanup::Cleanup;
+use crate::cold::Cold;
+use crate::cli::{CliCfg, OwnerKey};
+use crate::dirmeta::DirOwners;
+use crate::forecast::Forecast;
+use crate::hist::SizeHists;
+use crate::history::{self, HistoryRow};
+use crate::leftover::Leftovers;
+use crate::namenode::NameNode;
+use crate::owner::PerOwner;
+use crate::partition::Partitions;
+use crate::pathdate::PathDates;
+use crate::recent::Recent;
+use crate::retention::Retention;
+use crate::smallfiles;
+use crate::snapshot::Snapshots;
+use crate::stale;
+use crate::template;
+use crate::trash::Trash;
+use crate::xtab::OwnerXtab;
 
 pub fn dur_to_str(dur: Duration) -> String {
     const NS: u128 = 1_000_000_000;
@@ -41,6 +66,10 @@ pub struct FileStat {
     pub file_type: char,
     pub size: u64,
     pub mod_time: u64,
+    /// 0 until known, process_entry estimates it from the block size
+    pub blocks: u64,
+    /// last access in ms since the epoch, when the listing has it
+    pub access_time: Option<u64>,
 }
 
 #[derive(Debug)]
@@ -48,6 +77,58 @@ pub struct FileInfo {
     pub path: PathBuf,
     pub stat: FileStat,
     pub user: String,
+    pub group: Option<String>,
+    /// permission string like drwxr-x---, only oiv listings carry it
+    pub perms: Option<String>,
+}
+
+/// A directory's own metadata from its D record, kept apart from the stats of its content.
+pub struct DirMeta {
+    pub mtime: u64,
+    pub owner: String,
+    pub group: Option<String>,
+    pub perms: Option<String>,
+}
+
+impl DirMeta {
+    pub fn of(fi: &FileInfo) -> Self {
+        DirMeta {
+            mtime: fi.stat.mod_time,
+            owner: fi.user.clone(),
+            group: fi.group.clone(),
+            perms: fi.perms.clone(),
+        }
+    }
+}
+
+/// the directory `depth` levels below the root that holds `path`, e.g. depth 1 of /data/x/f is /data
+pub fn dir_at_depth(path: &Path, depth: usize) -> Option<PathBuf> {
+    let names = path.components().filter(|c| matches!(c, Component::Normal(_))).count();
+    if names <= depth {
+        return None;
+    }
+    let mut dir = PathBuf::from("/");
+    for c in path.components().filter(|c| matches!(c, Component::Normal(_))).take(depth) {
+        dir.push(c);
+    }
+    Some(dir)
+}
+
+/// the top level directory holding `path` without copying it, None for entries directly under /
+pub fn top_dir(path: &Path) -> Option<&Path> {
+    path.ancestors().skip(1).find(|a| a.parent() == Some(Path::new("/")))
+}
+
+/// ms since the epoch for "yyyy-MM-dd HH:mm" at `tz_offset` minutes from UTC
+pub fn oiv_time(s: &str, tz_offset: i64) -> Result<u64> {
+    let bad = || anyhow!("time \"{}\" is not yyyy-MM-dd HH:mm", s);
+    let n = |r: std::ops::Range<usize>| s.get(r).and_then(|v| v.parse::<i64>().ok()).ok_or_else(bad);
+    if s.len() < 16 {
+        return Err(bad());
+    }
+    let days = days_from_civil(n(0..4)?, n(5..7)? as u32, n(8..10)? as u32);
+    let mins = days * 24 * 60 + n(11..13)? * 60 + n(14..16)? - tz_offset;
+    Ok((mins * 60 * 1000).max(0) as u64)
 }
 
 pub fn uri_to_path(path: &str) -> PathBuf {
@@ -73,7 +154,7 @@ pub fn uri_to_path(path: &str) -> PathBuf {
 
 impl FileInfo {
     pub fn new(raw_rec: StringRecord) -> Result<Self> {
-        if raw_rec.len() != 5 {
+        if raw_rec.len() < 5 {
             return Err(anyhow!("field count is wrong at {}", raw_rec.len()));
         }
         Ok(FileInfo {
@@ -96,15 +177,54 @@ impl FileInfo {
                 },
                 size: raw_rec[2].parse::<u64>().with_context(|| format!("unable parse number for size: \"{}\"", &raw_rec[2]))?,
                 mod_time: raw_rec[3].parse::<u64>().context("unable to parse mod time")?,
+                blocks: 0,
+                access_time: match raw_rec.get(6) {
+                    None | Some("") | Some("0") => None,
+                    Some(a) => Some(a.parse::<u64>().with_context(|| format!("unable to parse access time: \"{}\"", a))?),
+                },
                 // size: lexical::parse::<u64, _>(&raw_rec[2]).with_context(||format!("unable parse number for size: \"{}\"", &raw_rec[2]))?,
                 // mod_time: lexical::parse::<u64, _>(&raw_rec[3]).with_context(||format!("unable parse number for size: \"{}\"", &raw_rec[2]))?,
                 // size: 0,
                 // mod_time: 0,
             },
             user: raw_rec[4].to_string(),
+            group: raw_rec.get(5).filter(|g| !g.is_empty()).map(|g| g.to_string()),
+            perms: None,
+        })
+    }
 
+    /// a record of `hdfs oiv -p Delimited -delimiter '|'`:
+    /// Path|Replication|ModificationTime|AccessTime|PreferredBlockSize|BlocksCount|FileSize|NSQUOTA|DSQUOTA|Permission|UserName|GroupName
+    /// its times are local "yyyy-MM-dd HH:mm" of the oiv host, `tz_offset` is that zone in minutes from UTC
+    pub fn from_oiv(raw_rec: StringRecord, tz_offset: i64) -> Result<Self> {
+        if raw_rec.len() < 12 {
+            return Err(anyhow!("field count is wrong at {} for oiv delimited", raw_rec.len()));
+        }
+        // oiv writes 1970-01-01 00:00 when access times are turned off, whatever its zone
+        let access_time = if raw_rec[3].starts_with("1970-01-01 00:00") {
+            None
+        } else {
+            Some(oiv_time(&raw_rec[3], tz_offset).context("unable to parse access time")?)
+        };
+        Ok(FileInfo {
+            path: uri_to_path(&raw_rec[0]),
+            stat: FileStat {
+                file_type: match raw_rec[9].chars().next() {
+                    Some('d') => 'D',
+                    Some('l') => 'S',
+                    _ => 'F',
+                },
+                size: raw_rec[6].parse::<u64>().with_context(|| format!("unable parse number for size: \"{}\"", &raw_rec[6]))?,
+                mod_time: oiv_time(&raw_rec[2], tz_offset).context("unable to parse mod time")?,
+                blocks: raw_rec[5].parse::<u64>().with_context(|| format!("unable parse number for blocks: \"{}\"", &raw_rec[5]))?,
+                access_time,
+            },
+            user: raw_rec[10].to_string(),
+            group: Some(raw_rec[11].to_string()).filter(|g| !g.is_empty()),
+            perms: Some(raw_rec[9].to_string()),
         })
     }
+
     pub fn is_dir(self: &Self) -> bool {
         self.stat.file_type == 'D'
     }
@@ -121,9 +241,12 @@ pub struct _DirStat {
     pub size: u64,
     pub old: u64,
     pub new: u64,
+    pub small_cnt: u64,
+    pub small_size: u64,
+    pub blocks: u64,
 }
 
-const GREEK_SUFFIXES: &[&str] = &["B", "KB", "MB", "GB", "TB", "PB", "EB", "ZB", "YB"];
+pub const GREEK_SUFFIXES: &[&str] = &["B", "KB", "MB", "GB", "TB", "PB", "EB", "ZB", "YB"];
 
 pub fn greek(v: f64) -> String {
     let mut number = v;
@@ -146,9 +269,21 @@ pub fn greek(v: f64) -> String {
     return format!("{:<5}{}", s, GREEK_SUFFIXES[multi]);
 }
 
+pub fn pct(part: u64, whole: u64) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 * 100.0 / whole as f64
+    }
+}
+
 pub struct DirStat {
     pub direct: _DirStat,
     pub recurse: _DirStat,
+    /// recursive bytes in each of the AGE_CLASSES
+    pub age_bytes: [u64; AGE_CLASS_CNT],
+    /// the directory's own D record, None while it is undeclared
+    pub meta: Option<DirMeta>,
 }
 
 impl DirStat {
@@ -159,42 +294,52 @@ impl DirStat {
                 size: 0,
                 old: 0,
                 new: 0,
+                small_cnt: 0,
+                small_size: 0,
+                blocks: 0,
             },
             recurse: _DirStat {
                 entry_cnt: 0,
                 size: 0,
                 old: 0,
                 new: 0,
+                small_cnt: 0,
+                small_size: 0,
+                blocks: 0,
             },
+            age_bytes: [0; AGE_CLASS_CNT],
+            meta: None,
         }
     }
-    pub fn new(stat: &FileStat) -> Self {
+    /// a declared directory, its content stats start out empty
+    pub fn new(meta: DirMeta) -> Self {
         DirStat {
-            direct: _DirStat {
-                entry_cnt: 1,
-                size: stat.size,
-                old: stat.mod_time,
-                new: stat.mod_time,
-            },
-            recurse: _DirStat {
-                entry_cnt: 1,
-                size: stat.size,
-                old: stat.mod_time,
-                new: stat.mod_time,
-            },
+            meta: Some(meta),
+            ..DirStat::empty()
         }
     }
-    pub fn merge(self: &mut Self, stat: &FileStat, direct: bool) {
+    pub fn merge(self: &mut Self, stat: &FileStat, direct: bool, small: bool) {
+        // an empty stat has no oldest time yet
+        self.recurse.old = if self.recurse.entry_cnt == 0 { stat.mod_time } else { min(self.recurse.old, stat.mod_time) };
         self.recurse.entry_cnt += 1;
         self.recurse.size += stat.size;
-        self.recurse.old = min(self.recurse.old, stat.mod_time);
         self.recurse.new = max(self.recurse.new, stat.mod_time);
+        self.recurse.blocks += stat.blocks;
+        if small {
+            self.recurse.small_cnt += 1;
+            self.recurse.small_size += stat.size;
+        }
 
         if direct {
+            self.direct.old = if self.direct.entry_cnt == 0 { stat.mod_time } else { min(self.direct.old, stat.mod_time) };
             self.direct.entry_cnt += 1;
             self.direct.size += stat.size;
-            self.direct.old = min(self.direct.old, stat.mod_time);
             self.direct.new = max(self.direct.new, stat.mod_time);
+            self.direct.blocks += stat.blocks;
+            if small {
+                self.direct.small_cnt += 1;
+                self.direct.small_size += stat.size;
+            }
         }
     }
     pub fn merge_file_stat(self: &mut Self, stat: &FileStat, direct: bool) {
@@ -224,11 +369,11 @@ impl DirStat {
 
 
 #[derive(Debug)]
-struct Tracked<T> {
-    size: u64,
-    track: T,
-    old: u64,
-    new: u64,
+pub struct Tracked<T> {
+    pub size: u64,
+    pub track: T,
+    pub old: u64,
+    pub new: u64,
 }
 
 impl<T> Eq for Tracked<T> {}
@@ -251,7 +396,7 @@ impl<T> PartialEq for Tracked<T> {
     }
 }
 
-fn track_top_n<T: Clone>(heap: &mut BinaryHeap<Tracked<T>>, p: &T, s: u64, limit: usize, old: u64, new: u64) {
+pub fn track_top_n<T: Clone>(heap: &mut BinaryHeap<Tracked<T>>, p: &T, s: u64, limit: usize, old: u64, new: u64) {
     if limit > 0 {
         if heap.len() < limit {
             heap.push(Tracked {
@@ -275,7 +420,7 @@ fn track_top_n<T: Clone>(heap: &mut BinaryHeap<Tracked<T>>, p: &T, s: u64, limit
 }
 
 
-fn to_sort_vec<T: Clone>(heap: &BinaryHeap<Tracked<T>>) -> Vec<Tracked<T>> {
+pub fn to_sort_vec<T: Clone>(heap: &BinaryHeap<Tracked<T>>) -> Vec<Tracked<T>> {
     let mut v = Vec::with_capacity(heap.len());
     for i in heap {
         v.push(Tracked {
@@ -324,14 +469,42 @@ pub struct Tracking {
     root: PathBuf,
     largest_file: BinaryHeap<Tracked<PathBuf>>,
     largest_user: HashMap<String,(u64, u64)>,
-    largest_time: BTreeMap<u64, u64>,
+    largest_time: BTreeMap<u64, (u64, u64)>,
+    calendar: Calendar,
     num_entries: u64,
+    /// F and S records, what the per owner file counts are shares of
+    num_files: u64,
     total_file_space: u64,
     parent_not_found: u64,
     parent_filled_in_later: u64,
+    /// D records for a directory that already had one, the later one wins
+    dirs_declared_twice: u64,
+    chargeback: Option<Chargeback>,
+    owner_xtab: Option<OwnerXtab>,
+    per_user: Option<PerOwner>,
+    per_group: Option<PerOwner>,
+    size_hists: Option<SizeHists>,
+    small_file_size: u64,
+    namenode: Option<NameNode>,
+    recent: Option<Recent>,
+    now_ms: u64,
+    cold: Option<Cold>,
+    retention: Option<Retention>,
+    cleanup: Option<Cleanup>,
+    forecast: Option<Forecast>,
+    partitions: Option<Partitions>,
+    path_dates: Option<PathDates>,
+    classes: Option<Classes>,
+    trash: Option<Trash>,
+    snapshots: Snapshots,
+    leftovers: Option<Leftovers>,
+    /// directories synthesized for entries whose parent was not declared (yet)
+    undeclared: HashSet<PathBuf>,
+    owner_anomalies: Option<OwnerAnomalies>,
+    dir_owners: Option<DirOwners>,
 }
 
-fn print_tp_size(now: SystemTime, tp: &Tracked<PathBuf>) {
+pub fn print_tp_size(now: SystemTime, tp: &Tracked<PathBuf>) {
     println!(
         "{} {}  age:[{}-{} D: {}]",
         greek(tp.size as f64),
@@ -344,24 +517,82 @@ fn print_tp_size(now: SystemTime, tp: &Tracked<PathBuf>) {
 
 
 impl Tracking {
-    pub fn new() -> Tracking {
-        Tracking {
+    pub fn new(cli: &CliCfg) -> Result<Tracking> {
+        // one now for every report so they all agree on ages
+        let now = now_ms();
+        Ok(Tracking {
             dtree: HashMap::new(),
             root: PathBuf::from("/"),
             num_entries: 0,
+            num_files: 0,
             total_file_space: 0,
             largest_file: BinaryHeap::new(),
             largest_user: HashMap::new(),
             largest_time: BTreeMap::new(),
+            calendar: Calendar::new(cli.time_bucket, cli.tz_offset),
             parent_not_found: 0,
             parent_filled_in_later: 0,
-        }
+            dirs_declared_twice: 0,
+            chargeback: Chargeback::from_cli(cli),
+            owner_xtab: cli.xtab_depth.map(OwnerXtab::new),
+            per_user: match (cli.per_user_top, &cli.owner_report_dir, cli.owner_report_by) {
+                (Some(n), _, _) => Some(PerOwner::new(n, now)),
+                (None, Some(_), OwnerKey::User) => Some(PerOwner::new(cli.top_n, now)),
+                _ => None,
+            },
+            now_ms: now,
+            retention: match &cli.retention_rules {
+                Some(p) => Some(Retention::load(p, now)?),
+                None => None,
+            },
+            cleanup: Cleanup::from_cli(cli, now)?,
+            forecast: if cli.forecast { Some(Forecast::new()) } else { None },
+            trash: if cli.trash { Some(Trash::new(now, cli.trash_interval.as_millis() as u64, cli.tz_offset)) } else { None },
+            snapshots: Snapshots::new(cli.snapshots),
+            undeclared: HashSet::new(),
+            owner_anomalies: if cli.owner_anomalies { Some(OwnerAnomalies::new(cli.anomaly_depth)) } else { None },
+            leftovers: if cli.leftovers { Some(Leftovers::new()) } else { None },
+            dir_owners: if cli.dir_meta { Some(DirOwners::new()) } else { None },
+            classes: if cli.classify || cli.class_rules.is_some() { Some(Classes::load(cli.class_rules.as_deref())?) } else { None },
+            partitions: if cli.partitions { Some(Partitions::new()) } else { None },
+            path_dates: if cli.path_date.is_empty() {
+                None
+            } else {
+                Some(PathDates::new(
+                    &cli.path_date,
+                    cli.path_date_age.map(|a| a.as_millis() as u64),
+                    now,
+                    &Calendar::new(cli.time_bucket, cli.tz_offset),
+                )?)
+            },
+            cold: cli.cold_age.map(|a| Cold::new(now, a.as_millis() as u64)),
+            recent: cli.recent_window.map(|w| Recent::new(now, w.as_millis() as u64, cli.top_n)),
+            namenode: if cli.namenode { Some(NameNode::new(cli.nn_inode_bytes, cli.nn_block_bytes)) } else { None },
+            small_file_size: cli.small_file_size.unwrap_or(cli.block_size),
+            size_hists: if cli.size_hist { Some(SizeHists::new(cli.block_size, cli.hist_depth)) } else { None },
+            per_group: match (&cli.owner_report_dir, cli.owner_report_by) {
+                (Some(_), OwnerKey::Group) => Some(PerOwner::new(cli.per_user_top.unwrap_or(cli.top_n), now)),
+                _ => None,
+            },
+        })
     }
 
-    pub fn process_entry(self: &mut Self, fi: FileInfo, cli: &CliCfg) -> Result<()> {
+    pub fn process_entry(self: &mut Self, mut fi: FileInfo, cli: &CliCfg) -> Result<()> {
+        if self.snapshots.take(&fi) {
+            return Ok(());
+        }
 
         self.total_file_space += fi.stat.size;
         self.num_entries += 1;
+        if fi.is_file() && fi.stat.blocks == 0 {
+            fi.stat.blocks = fi.stat.size.div_ceil(cli.block_size);
+        }
+        if let Some(nn) = &mut self.namenode {
+            nn.record(&fi);
+        }
+        if let Some(l) = &mut self.leftovers {
+            l.record(&fi);
+        }
 
         if let Some(user_entry) = self.largest_user.get_mut(&fi.user) {
             user_entry.0 += fi.stat.size;
@@ -371,29 +602,84 @@ impl Tracking {
         }
 
         if fi.is_dir() {
+            if let Some(c) = &mut self.cleanup {
+                c.record(&fi)?;
+            }
 
             if let Some(entry) = self.dtree.get_mut(&fi.path) {
-                if entry.direct.old == 0 {
+                if self.undeclared.remove(&fi.path) {
                     self.parent_filled_in_later += 1;
                 } else {
-                    self.parent_filled_in_later += 1;
-                    eprintln!("weird - not a fillin path {} with old: {}", fi.path.to_string_lossy(), entry.direct.old);
+                    self.dirs_declared_twice += 1;
+                    eprintln!("weird - not a fillin path {} declared twice", fi.path.to_string_lossy());
                 }
-                entry.merge(&fi.stat, false);  
+                entry.meta = Some(DirMeta::of(&fi));
             } else {
-                self.dtree.insert(fi.path, DirStat::new(&fi.stat));
+                let meta = DirMeta::of(&fi);
+                self.dtree.insert(fi.path, DirStat::new(meta));
             }
         } else if fi.is_file() || fi.is_sym() {
+            self.num_files += 1;
             let mut direct_parent = true;
-            const WEEK_BUCKET_JAVA_MS: u64 = 1000 * 3600 * 24 * 7;
-            let week_bucket = (fi.stat.mod_time / (WEEK_BUCKET_JAVA_MS)) * WEEK_BUCKET_JAVA_MS;
-            if let Some(entry) = self.largest_time.get_mut(&week_bucket) {
-                *entry += fi.stat.size;
+            let small = fi.is_file() && fi.stat.size < self.small_file_size;
+            let age = age_class(self.now_ms, fi.stat.mod_time);
+            let time_bucket = self.calendar.bucket_start(fi.stat.mod_time);
+            if let Some(entry) = self.largest_time.get_mut(&time_bucket) {
+                entry.0 += fi.stat.size;
+                entry.1 += 1;
             } else {
-                self.largest_time.insert(week_bucket, fi.stat.size);
+                self.largest_time.insert(time_bucket, (fi.stat.size, 1));
             }
 
             track_top_n(&mut self.largest_file, &fi.path, fi.stat.size, cli.top_n, fi.stat.mod_time, 0);
+            if let Some(cb) = &mut self.chargeback {
+                cb.record(&fi);
+            }
+            if let Some(xt) = &mut self.owner_xtab {
+                xt.record(&fi);
+            }
+            if let Some(pu) = &mut self.per_user {
+                pu.record(&fi.user, &fi);
+            }
+            if let Some(pg) = &mut self.per_group {
+                pg.record(fi.group.as_deref().unwrap_or("-"), &fi);
+            }
+            if let (Some(sh), true) = (&mut self.size_hists, fi.is_file()) {
+                sh.record(&fi);
+            }
+            if let Some(r) = &mut self.recent {
+                r.record(&fi);
+            }
+            if let (Some(c), true) = (&mut self.cold, fi.is_file()) {
+                c.record(&fi);
+            }
+            if let (Some(r), true) = (&mut self.retention, fi.is_file()) {
+                r.record(&fi);
+            }
+            if let Some(c) = &mut self.cleanup {
+                c.record(&fi)?;
+            }
+            if let Some(f) = &mut self.forecast {
+                f.record(&fi);
+            }
+            if let (Some(p), true) = (&mut self.partitions, fi.is_file()) {
+                p.record(&fi);
+            }
+            if let (Some(pd), true) = (&mut self.path_dates, fi.is_file()) {
+                pd.record(&fi, &self.calendar);
+            }
+            if let Some(c) = &mut self.classes {
+                c.record(&fi);
+            }
+            if let Some(t) = &mut self.trash {
+                t.record(&fi);
+            }
+            if let Some(oa) = &mut self.owner_anomalies {
+                oa.record(&fi);
+            }
+            if let Some(d) = &mut self.dir_owners {
+                d.record(&fi);
+            }
             let mut p_path = fi.path.as_path();
             loop {
                 if let Some(_p_path) = p_path.parent() {
@@ -402,13 +688,16 @@ impl Tracking {
 
                     match self.dtree.get_mut(p_path) {
                         Some(stat) => {
-                            stat.merge(&fi.stat, direct_parent);
+                            stat.merge(&fi.stat, direct_parent, small);
+                            stat.age_bytes[age] += fi.stat.size;
                         },
                         None => {
-                            let p_path_buf = p_path.to_path_buf();
                             self.parent_not_found += 1;
-                            eprintln!("parent not found for (adding with empty stats):  {} from: {}", p_path_buf.to_str().unwrap(), fi.path.to_str().unwrap());
-                            self.dtree.insert(p_path.to_path_buf(), DirStat::empty());
+                            let mut stat = DirStat::empty();
+                            stat.merge(&fi.stat, direct_parent, small);
+                            stat.age_bytes[age] += fi.stat.size;
+                            self.dtree.insert(p_path.to_path_buf(), stat);
+                            self.undeclared.insert(p_path.to_path_buf());
                         },
                     }
                     direct_parent = false;
@@ -427,7 +716,55 @@ impl Tracking {
     }
 
    
-    pub fn walk_and_heap(self: &Self, cli: &CliCfg) {
+    /// local midnight of --listing-date, or now
+    fn listing_ms(&self, cli: &CliCfg) -> u64 {
+        cli.listing_date.map_or(self.now_ms, |d| self.calendar.days_to_ms(d))
+    }
+
+    /// totals, every user and the directories down to --history-depth for this run's listing date
+    fn history_rows(&self, cli: &CliCfg) -> Vec<HistoryRow> {
+        let date = self.listing_ms(cli);
+        let row = |kind: &str, name: String, bytes: u64, files: u64| HistoryRow {
+            date,
+            kind: kind.to_string(),
+            name,
+            bytes,
+            files,
+        };
+        let mut rows = vec![row("total", "/".to_string(), self.total_file_space, self.num_entries)];
+        for (u, (bytes, files)) in &self.largest_user {
+            rows.push(row("user", u.clone(), *bytes, *files));
+        }
+        for (p, s) in &self.dtree {
+            if (2..=cli.history_depth + 1).contains(&p.components().count()) {
+                rows.push(row("dir", p.to_string_lossy().to_string(), s.recurse.size, s.recurse.entry_cnt));
+            }
+        }
+        rows
+    }
+
+    /// Directories never declared by a D record, the highest of each missing subtree with what hangs under it.
+    /// Many of these point at an incomplete export rather than entries listed before their parent.
+    fn undeclared_report(&self, top_n: usize) {
+        let mut tops = self
+            .undeclared
+            .iter()
+            .filter(|p| p.parent().is_none_or(|pp| !self.undeclared.contains(pp)))
+            .filter_map(|p| self.dtree.get(p).map(|s| (p, s)))
+            .collect::<Vec<_>>();
+        tops.sort_by_key(|(_, s)| std::cmp::Reverse(s.recurse.size));
+        println!(
+            "\nUndeclared directories - {} never declared in {} missing subtrees, {} declared after their entries",
+            self.undeclared.len(),
+            tops.len(),
+            self.parent_filled_in_later
+        );
+        for (p, s) in tops.iter().take(top_n) {
+            println!("{} {:8} {}", greek(s.recurse.size as f64), s.recurse.entry_cnt, p.display());
+        }
+    }
+
+    pub fn walk_and_heap(self: &mut Self, cli: &CliCfg) {
         let mut top_size: BinaryHeap<Tracked<PathBuf>> = BinaryHeap::new();
         let mut top_cnt: BinaryHeap<Tracked<PathBuf>> = BinaryHeap::new();
         let mut top_size_recur: BinaryHeap<Tracked<PathBuf>> = BinaryHeap::new();
@@ -453,7 +790,13 @@ impl Tracking {
         type PrintTypePath = for<'r> fn(SystemTime, &'r Tracked<PathBuf>);
 
         println!("Processed {} entry Total space: {}", self.num_entries, greek(self.total_file_space as f64));
-        println!("Parent not found in time: {}  Parent filled in later {}", self.parent_not_found, self.parent_filled_in_later);
+        println!(
+            "Parent not found in time: {}  Parent filled in later {}  Declared twice {}",
+            self.parent_not_found, self.parent_filled_in_later, self.dirs_declared_twice
+        );
+        if !self.undeclared.is_empty() {
+            self.undeclared_report(cli.top_n);
+        }
 
         println!("\nTop usage by user ID");
         for (n, a) in to_sort_vec_name_size(&self.largest_user).iter().take(cli.top_n) {
@@ -464,6 +807,9 @@ impl Tracking {
         for (n, a) in to_sort_vec_name_cnt(&self.largest_user).iter().take(cli.top_n) {
             println!("{:8} {}", a, &n);
         }
+        if let Some(c) = &self.classes {
+            c.report(cli.top_n);
+        }
 
         for rep in [
             ("\nTop directories based on file sizes directly in them", print_tp_size as PrintTypePath, &top_size),
@@ -478,15 +824,97 @@ impl Tracking {
             }
         }
 
-        fn largest_time_to_sort_vec(time_size: &BTreeMap<u64,u64>) -> Vec<(u64,u64)> {
-            let mut v = time_size.iter().map(|(t,s)| (*t,*s)).collect::<Vec<(u64,u64)>>();
-            v.sort_by(|a,b| b.0.partial_cmp(&a.0).unwrap());
+        fn largest_time_to_sort_vec(time_size: &BTreeMap<u64,(u64,u64)>) -> Vec<(u64,(u64,u64))> {
+            let mut v = time_size.iter().map(|(t,s)| (*t,*s)).collect::<Vec<(u64,(u64,u64))>>();
+            v.sort_by_key(|e| std::cmp::Reverse(e.1.0));
             v
         }
-        
-        println!("\nTop timeframes based on size (time->size)");
-        for tf in largest_time_to_sort_vec(&self.largest_time) {
-            println!("{} {}", get_age(now, tf.0), greek(tf.1 as f64));
+
+        println!("\nTop timeframes based on size ({} buckets)", format!("{:?}", self.calendar.bucket).to_lowercase());
+        for (start, (size, cnt)) in largest_time_to_sort_vec(&self.largest_time).iter().take(cli.top_n) {
+            println!("{} {:8} {}", greek(*size as f64), cnt, self.calendar.fmt_bucket(*start));
+        }
+
+        if let Some(cb) = &self.chargeback {
+            cb.report(cli.top_n);
+        }
+        if let Some(xt) = &self.owner_xtab {
+            xt.report(cli.top_n);
+        }
+        if let (Some(pu), Some(_)) = (&self.per_user, cli.per_user_top) {
+            pu.report(cli.top_n);
+        }
+        if let Some(sh) = &self.size_hists {
+            sh.report(cli.top_n);
+        }
+        if cli.small_files {
+            // compacting into files below the small file size would not reduce anything
+            smallfiles::report(&self.dtree, self.small_file_size, cli.compact_target.max(self.small_file_size), cli.top_n);
+        }
+        if let Some(nn) = &self.namenode {
+            nn.report(&self.dtree, cli.top_n);
+        }
+        if let Some(age) = cli.stale_age {
+            stale::report(&self.dtree, self.now_ms, age.as_millis() as u64, cli.top_n);
+        }
+        if let Some(r) = &self.recent {
+            r.report();
+        }
+        if let Some(c) = &self.cold {
+            c.report(&self.dtree, cli.top_n);
+        }
+        if let Some(r) = &self.retention {
+            r.report(cli.top_n);
+        }
+        if let Some(p) = &self.partitions {
+            p.report(cli.top_n);
+        }
+        if cli.templates {
+            template::report(&self.dtree, cli.top_n);
+        }
+        if let Some(pd) = &self.path_dates {
+            pd.report();
+        }
+        if let Some(t) = &self.trash {
+            t.report(cli.top_n);
+        }
+        self.snapshots.report(cli.top_n);
+        if let Some(l) = &self.leftovers {
+            l.report(cli.top_n);
+        }
+        if let Some(h) = &cli.history {
+            if let Err(e) = history::append(h, &self.history_rows(cli)) {
+                eprintln!("unable to record run in history: {:#}", e);
+            }
+        }
+        if let Some(f) = &self.forecast {
+            f.report(cli, &self.calendar, self.listing_ms(cli));
+        }
+        if let Some(c) = &mut self.cleanup {
+            if let Err(e) = c.write_list() {
+                eprintln!("unable to write cleanup list: {:#}", e);
+            }
+        }
+        if let Some(d) = &self.dir_owners {
+            d.report(&self.dtree, self.now_ms, cli.touched_window.as_millis() as u64, cli.old_content_age.as_millis() as u64, cli.top_n);
+        }
+        if let Some(oa) = &self.owner_anomalies {
+            oa.report(&self.dtree, cli.top_n);
+        }
+        if cli.age_profile {
+            age::report(&self.dtree, cli.top_n);
+        }
+        if let Some(dir) = &cli.owner_report_dir {
+            let owners = match cli.owner_report_by {
+                OwnerKey::User => &self.per_user,
+                OwnerKey::Group => &self.per_group,
+            };
+            if let Some(po) = owners {
+                match po.write_reports(dir, self.num_files, self.total_file_space) {
+                    Ok(()) => println!("\nWrote {} owner reports to {}", po.owners.len(), dir.display()),
+                    Err(e) => eprintln!("unable to write owner reports: {:#}", e),
+                }
+            }
         }
         
 
@@ -502,3 +930,66 @@ impl Tracking {
         // }
     }
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    const HOUR: u64 = 3600 * 1000;
+
+    /// a listing entry for the report tests, `kind` is D, F or S
+    pub(crate) fn entry(kind: &str, path: &str, size: u64, mtime: u64, user: &str) -> FileInfo {
+        let rec = StringRecord::from(vec![kind, &format!("hdfs://nn{}", path), &size.to_string(), &mtime.to_string(), user]);
+        FileInfo::new(rec).unwrap()
+    }
+
+    fn oiv(line: &str, tz_offset: i64) -> Result<FileInfo> {
+        FileInfo::from_oiv(StringRecord::from(line.split('|').collect::<Vec<_>>()), tz_offset)
+    }
+
+    #[test]
+    fn oiv_times() {
+        let day = days_from_civil(2021, 7, 1) as u64 * 24 * HOUR;
+        assert_eq!(oiv_time("2021-07-01 03:00", 0).unwrap(), day + 3 * HOUR);
+        // local times west of UTC are later in UTC, east of it earlier
+        assert_eq!(oiv_time("2021-07-01 03:00", -300).unwrap(), day + 8 * HOUR);
+        assert_eq!(oiv_time("2021-07-01 03:00", 330).unwrap(), day + 3 * HOUR - 330 * 60 * 1000);
+        assert_eq!(oiv_time("1970-01-01 00:00", 60).unwrap(), 0);
+        assert!(oiv_time("2021-07-01", 0).is_err());
+        assert!(oiv_time("2021-07-01 3:00pm", 0).is_err());
+    }
+
+    #[test]
+    fn oiv_types() {
+        let d = oiv("/user|0|2024-08-09 19:31|1970-01-01 00:00|0|0|0|-1|-1|drwxr-xr-x|hdfs|hadoop", 0).unwrap();
+        assert!(d.is_dir());
+        assert_eq!(d.path, PathBuf::from("/user"));
+        assert_eq!(d.perms.as_deref(), Some("drwxr-xr-x"));
+        let l = oiv("/l|0|2024-08-09 19:31|2024-08-09 19:31|0|0|0|0|0|lrwxrwxrwx|bob|", 0).unwrap();
+        assert!(l.is_sym());
+        assert_eq!(l.group, None);
+        let f = oiv("/user/bob/f|3|2024-08-09 19:31|2024-08-10 08:00|134217728|2|200000000|0|0|-rw-r--r--|bob|eng", 0).unwrap();
+        assert!(f.is_file());
+        assert_eq!((f.stat.size, f.stat.blocks), (200000000, 2));
+        assert_eq!((f.user.as_str(), f.group.as_deref()), ("bob", Some("eng")));
+    }
+
+    #[test]
+    fn oiv_no_access_time() {
+        let line = "/f|3|2024-08-09 19:31|1970-01-01 00:00|134217728|1|10|0|0|-rw-r--r--|bob|eng";
+        for tz in [0, -300, 330] {
+            assert_eq!(oiv(line, tz).unwrap().stat.access_time, None, "tz {}", tz);
+        }
+        let f = oiv("/f|3|2024-08-09 19:31|2024-08-10 08:00|134217728|1|10|0|0|-rw-r--r--|bob|eng", -300).unwrap();
+        let day = days_from_civil(2024, 8, 10) as u64 * 24 * HOUR;
+        assert_eq!(f.stat.access_time, Some(day + 13 * HOUR));
+        assert_eq!(f.stat.mod_time, day - 24 * HOUR + 19 * HOUR + 31 * 60 * 1000 + 5 * HOUR);
+    }
+
+    #[test]
+    fn oiv_bad_records() {
+        assert!(oiv("/f|3|2024-08-09 19:31|1970-01-01 00:00|134217728|1|10", 0).is_err());
+        assert!(oiv("/f|3|2024-08-09 19:31|1970-01-01 00:00|134217728|1|ten|0|0|-rw-r--r--|bob|eng", 0).is_err());
+        assert!(oiv("/f|3|yesterday|1970-01-01 00:00|134217728|1|10|0|0|-rw-r--r--|bob|eng", 0).is_err());
+    }
+}
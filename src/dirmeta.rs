@@ -0,0 +1,117 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use crate::data::{get_age, greek, pct, DirStat, FileInfo};
+
+/// Reports comparing each directory's own D record with the content under it:
+/// content owned by someone other than the directory owner, and directories modified
+/// within `touched_ms` whose newest content is older than `old_ms` - usually deletes or renames.
+pub struct DirOwners {
+    /// bytes and entries directly in each directory per owner, set against the directory owner
+    /// only at report time as a D record may come after its content
+    direct: HashMap<PathBuf, HashMap<String, (u64, u64)>>,
+}
+
+impl DirOwners {
+    pub fn new() -> Self {
+        DirOwners { direct: HashMap::new() }
+    }
+
+    pub fn record(&mut self, fi: &FileInfo) {
+        let parent = match fi.path.parent() {
+            Some(p) => p,
+            None => return,
+        };
+        let owners = match self.direct.get_mut(parent) {
+            Some(o) => o,
+            None => self.direct.entry(parent.to_path_buf()).or_default(),
+        };
+        let o = match owners.get_mut(&fi.user) {
+            Some(o) => o,
+            None => owners.entry(fi.user.clone()).or_default(),
+        };
+        o.0 += fi.stat.size;
+        o.1 += 1;
+    }
+
+    /// bytes and entries directly in `dir` owned by someone other than `owner`
+    fn foreign(&self, dir: &Path, owner: &str) -> (u64, u64) {
+        self.direct.get(dir).map_or((0, 0), |owners| {
+            owners.iter().filter(|(o, _)| *o != owner).fold((0, 0), |s, (_, c)| (s.0 + c.0, s.1 + c.1))
+        })
+    }
+
+    pub fn report(&self, dtree: &HashMap<PathBuf, DirStat>, now_ms: u64, touched_ms: u64, old_ms: u64, top_n: usize) {
+        let now = SystemTime::now();
+
+        let mut foreign = dtree
+            .iter()
+            .filter_map(|(p, s)| s.meta.as_ref().map(|m| (p, s, m, self.foreign(p, &m.owner))))
+            .filter(|(_, _, _, f)| f.1 > 0)
+            .collect::<Vec<_>>();
+        foreign.sort_by_key(|(_, _, _, f)| std::cmp::Reverse(f.0));
+        println!("\nDirectories holding content owned by someone other than the directory owner - {} directories", foreign.len());
+        println!("{:>9} {:>8} {:>6} {:>10} {:>10} directory", "foreign", "entries", "share", "dir owner", "perms");
+        for (p, s, m, f) in foreign.iter().take(top_n) {
+            println!(
+                "{} {:8} {:5.1}% {:>10} {:>10} {}",
+                greek(f.0 as f64),
+                f.1,
+                pct(f.0, s.direct.size),
+                m.owner,
+                m.perms.as_deref().unwrap_or("-"),
+                p.display()
+            );
+        }
+
+        let (touched_after, old_before) = (now_ms.saturating_sub(touched_ms), now_ms.saturating_sub(old_ms));
+        let mut touched = dtree
+            .iter()
+            .filter_map(|(p, s)| s.meta.as_ref().map(|m| (p, s, m.mtime)))
+            .filter(|(_, s, mtime)| *mtime >= touched_after && s.recurse.entry_cnt > 0 && s.recurse.new < old_before)
+            .collect::<Vec<_>>();
+        touched.sort_by_key(|(_, s, _)| std::cmp::Reverse(s.recurse.size));
+        println!(
+            "\nDirectories modified in the last {} with nothing newer than {} under them - {} directories",
+            get_age(now, touched_after),
+            get_age(now, old_before),
+            touched.len()
+        );
+        for (p, s, mtime) in touched.iter().take(top_n) {
+            println!(
+                "{} {:8} {}  modified:[{}] newest:[{}]",
+                greek(s.recurse.size as f64),
+                s.recurse.entry_cnt,
+                p.display(),
+                get_age(now, *mtime),
+                get_age(now, s.recurse.new)
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::tests::entry;
+
+    fn file(path: &str, size: u64, user: &str) -> FileInfo {
+        entry("F", path, size, 1_600_000_000_000, user)
+    }
+
+    #[test]
+    fn foreign_without_the_dir_record() {
+        // content seen before the D record of /a still counts once its owner is known
+        let mut d = DirOwners::new();
+        for fi in [file("/a/f1", 10, "bob"), file("/a/f2", 20, "alice"), file("/a/f3", 5, "carol"), file("/a/b/f4", 7, "alice")] {
+            d.record(&fi);
+        }
+        assert_eq!(d.foreign(Path::new("/a"), "bob"), (25, 2));
+        assert_eq!(d.foreign(Path::new("/a"), "alice"), (15, 2));
+        assert_eq!(d.foreign(Path::new("/a/b"), "alice"), (0, 0));
+        assert_eq!(d.foreign(Path::new("/c"), "bob"), (0, 0));
+    }
+}
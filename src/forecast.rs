@@ -0,0 +1,201 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    calendar::{Calendar, DAY_MS},
+    cli::CliCfg,
+    data::{greek, pct, top_dir, FileInfo},
+    history::{self, signed_greek},
+    pattern::path_names,
+};
+
+type Series = BTreeMap<u64, u64>;
+
+/// Growth rates per top level directory, user and overall, projected against a cluster capacity.
+/// Rates are fitted to the recorded history when it has at least two runs and otherwise to
+/// the bytes modified per day, which cannot see deletes or overwritten files.
+pub struct Forecast {
+    /// bytes modified per day since the epoch overall, per user and per top level directory
+    total: Series,
+    users: HashMap<String, Series>,
+    dirs: HashMap<PathBuf, Series>,
+}
+
+/// least squares growth in bytes per day of (ms, bytes) points
+fn fit(points: &Series) -> Option<f64> {
+    let n = points.len() as f64;
+    let (mx, my) = points.iter().fold((0.0, 0.0), |(x, y), (t, b)| (x + *t as f64 / DAY_MS as f64 / n, y + *b as f64 / n));
+    let (sxy, sxx) = points.iter().fold((0.0, 0.0), |(sxy, sxx), (t, b)| {
+        let dx = *t as f64 / DAY_MS as f64 - mx;
+        (sxy + dx * (*b as f64 - my), sxx + dx * dx)
+    });
+    if sxx > 0.0 {
+        Some(sxy / sxx)
+    } else {
+        None
+    }
+}
+
+/// projections further out than this are shown as beyond it
+const HORIZON_DAYS: f64 = 100.0 * 365.0;
+
+/// time `headroom` bytes last at `rate` bytes per day from `asof`, None past the horizon
+fn full_at(asof: u64, headroom: u64, rate: f64) -> Option<u64> {
+    let days = headroom as f64 / rate;
+    if !days.is_finite() || days > HORIZON_DAYS {
+        return None;
+    }
+    asof.checked_add((days * DAY_MS as f64) as u64)
+}
+
+impl Forecast {
+    pub fn new() -> Self {
+        Forecast {
+            total: Series::new(),
+            users: HashMap::new(),
+            dirs: HashMap::new(),
+        }
+    }
+
+    pub fn record(&mut self, fi: &FileInfo) {
+        let (day, size) = (fi.stat.mod_time / DAY_MS as u64, fi.stat.size);
+        *self.total.entry(day).or_default() += size;
+        match self.users.get_mut(&fi.user) {
+            Some(s) => *s.entry(day).or_default() += size,
+            None => {
+                self.users.insert(fi.user.clone(), Series::from([(day, size)]));
+            }
+        }
+        if let Some(top) = top_dir(&fi.path) {
+            match self.dirs.get_mut(top) {
+                Some(s) => *s.entry(day).or_default() += size,
+                None => {
+                    self.dirs.insert(top.to_path_buf(), Series::from([(day, size)]));
+                }
+            }
+        }
+    }
+
+    /// size at the end of every day of the window, as if nothing modified was ever removed
+    fn mtime_series(&self, from: u64, to: u64) -> HashMap<(&str, &str), Series> {
+        let (first, last) = (from / DAY_MS as u64, to / DAY_MS as u64);
+        let keyed = std::iter::once((("total", "/"), &self.total))
+            .chain(self.users.iter().map(|(u, s)| (("user", u.as_str()), s)))
+            .chain(self.dirs.iter().filter_map(|(p, s)| Some((("dir", p.to_str()?), s))));
+        let mut all = HashMap::new();
+        for (key, days) in keyed {
+            let mut size = days.range(..first).map(|(_, b)| b).sum::<u64>();
+            let mut s = Series::new();
+            for d in first..=last {
+                size += days.get(&d).copied().unwrap_or(0);
+                s.insert((d + 1) * DAY_MS as u64 - 1, size);
+            }
+            all.insert(key, s);
+        }
+        all
+    }
+
+    pub fn report(&self, cli: &CliCfg, cal: &Calendar, now_ms: u64) {
+        let (window_ms, capacity, threshold) = (cli.forecast_window.as_millis() as u64, cli.capacity, cli.fill_threshold);
+        let rows = match cli.history.as_deref().map(history::load) {
+            Some(Ok(rows)) => rows,
+            Some(Err(e)) => {
+                eprintln!("unable to read history for the forecast: {:#}", e);
+                vec![]
+            }
+            None => vec![],
+        };
+        let last_run = rows.iter().map(|r| r.date).max().unwrap_or(0);
+        let mut from_history = history::series(&rows);
+        from_history.retain(|(kind, name), _| *kind != "dir" || path_names(Path::new(name)).len() == 1);
+        for s in from_history.values_mut() {
+            s.retain(|d, _| *d + window_ms >= last_run);
+        }
+        let runs = from_history.get(&("total", "/")).map_or(0, |s| s.len());
+
+        let (series, asof, source) = if runs >= 2 {
+            (from_history, last_run, format!("{} runs in the history", runs))
+        } else {
+            (self.mtime_series(now_ms.saturating_sub(window_ms), now_ms), now_ms, "bytes modified per day, deletes are not seen".to_string())
+        };
+        let fits = series
+            .iter()
+            .filter_map(|(k, s)| Some((*k, *s.values().last()?, fit(s)?)))
+            .collect::<Vec<_>>();
+
+        let (used, rate) = match fits.iter().find(|(k, _, _)| *k == ("total", "/")) {
+            Some((_, used, rate)) => (*used, *rate),
+            None => {
+                println!("\nForecast - not enough data to fit growth");
+                return;
+            }
+        };
+        let limit = capacity.map(|c| (c as f64 * threshold / 100.0) as u64);
+        // date the headroom to the threshold is used up when growing at `rate` bytes per day
+        let full_by = |rate: f64| match limit {
+            Some(l) if used >= l => "already past".to_string(),
+            Some(l) if rate > 0.0 => full_at(asof, l - used, rate).map_or(">100y".to_string(), |t| cal.fmt_date(t)),
+            Some(_) => "never".to_string(),
+            None => "-".to_string(),
+        };
+
+        println!("\nForecast as of {} from {}", cal.fmt_date(asof), source);
+        println!("used {} growing {} per 30 days", greek(used as f64), signed_greek((rate * 30.0) as i128));
+        if let (Some(c), Some(l)) = (capacity, limit) {
+            println!(
+                "capacity {} at {:.1}% ({}) is {:.1}% used - full by {}",
+                greek(c as f64),
+                threshold,
+                greek(l as f64),
+                pct(used, c),
+                full_by(rate)
+            );
+        }
+
+        for kind in ["dir", "user"] {
+            let mut top = fits.iter().filter(|((k, _), _, _)| *k == kind).collect::<Vec<_>>();
+            top.sort_by(|a, b| b.2.total_cmp(&a.2));
+            println!("\nFastest growing by {} - growth per 30 days, share of the growth, size, full by at that rate alone", kind);
+            for ((_, name), size, r) in top.iter().take(cli.top_n) {
+                println!(
+                    "{} {:5.1}% {} {:>12} {}",
+                    signed_greek((r * 30.0) as i128),
+                    if rate > 0.0 { r / rate * 100.0 } else { 0.0 },
+                    greek(*size as f64),
+                    full_by(*r),
+                    name
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_by_kind() {
+        let mut f = Forecast::new();
+        for (path, user) in [("/data/x/f", "bob"), ("/data/g", "bob"), ("/h", "alice")] {
+            f.record(&crate::data::tests::entry("F", path, 10, 10 * DAY_MS as u64, user));
+        }
+        let all = f.mtime_series(0, 20 * DAY_MS as u64);
+        let last = |k: (&str, &str)| all.get(&k).and_then(|s| s.values().last().copied());
+        assert_eq!(last(("total", "/")), Some(30));
+        assert_eq!(last(("user", "bob")), Some(20));
+        assert_eq!(last(("user", "alice")), Some(10));
+        assert_eq!(last(("dir", "/data")), Some(20));
+        assert_eq!(all.len(), 4);
+    }
+
+    #[test]
+    fn full_at_horizon() {
+        assert_eq!(full_at(0, 10, 1.0), Some(10 * DAY_MS as u64));
+        assert_eq!(full_at(0, 1 << 50, 1e-9), None);
+        assert_eq!(full_at(u64::MAX - 1, 10, 1.0), None);
+        assert_eq!(full_at(0, 10, f64::MIN_POSITIVE), None);
+    }
+}
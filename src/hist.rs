@@ -0,0 +1,131 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use crate::data::{dir_at_depth, greek, pct, FileInfo, GREEK_SUFFIXES};
+
+const BUCKETS: usize = 65;
+
+/// Log2 file size histogram, bucket i holds sizes in [2^(i-1), 2^i) and bucket 0 the empty files.
+pub struct SizeHist {
+    buckets: [(u64, u64); BUCKETS],
+    files: u64,
+    bytes: u64,
+    below_block: u64,
+}
+
+fn bucket_of(size: u64) -> usize {
+    (64 - size.leading_zeros()) as usize
+}
+
+/// 2^e as a whole number of 1024 based units, e.g. 1KB or 512MB
+fn pow2_label(e: usize) -> String {
+    format!("{}{}", 1u64 << (e % 10), GREEK_SUFFIXES[e / 10])
+}
+
+fn bucket_label(i: usize) -> String {
+    match i {
+        0 => "empty".to_string(),
+        _ => format!("{}-{}", pow2_label(i - 1), pow2_label(i)),
+    }
+}
+
+impl SizeHist {
+    pub fn new() -> Self {
+        SizeHist {
+            buckets: [(0, 0); BUCKETS],
+            files: 0,
+            bytes: 0,
+            below_block: 0,
+        }
+    }
+
+    pub fn add(&mut self, size: u64, block_size: u64) {
+        let b = &mut self.buckets[bucket_of(size)];
+        b.0 += 1;
+        b.1 += size;
+        self.files += 1;
+        self.bytes += size;
+        if size < block_size {
+            self.below_block += 1;
+        }
+    }
+
+    pub fn print(&self, indent: &str, block_size: u64) {
+        println!(
+            "{}{} files, {} - {:.1}% of files ({}) are below the block size of {}",
+            indent,
+            self.files,
+            greek(self.bytes as f64),
+            pct(self.below_block, self.files),
+            self.below_block,
+            greek(block_size as f64)
+        );
+        for (i, (cnt, bytes)) in self.buckets.iter().enumerate().filter(|(_, b)| b.0 > 0) {
+            println!(
+                "{}{:>11} {:10} {:5.1}% {} {:5.1}%",
+                indent,
+                bucket_label(i),
+                cnt,
+                pct(*cnt, self.files),
+                greek(*bytes as f64),
+                pct(*bytes, self.bytes)
+            );
+        }
+    }
+}
+
+/// File size histograms for the whole listing, each user and the directories down to a depth.
+pub struct SizeHists {
+    block_size: u64,
+    depth: usize,
+    global: SizeHist,
+    by_user: HashMap<String, SizeHist>,
+    by_dir: HashMap<PathBuf, SizeHist>,
+}
+
+impl SizeHists {
+    pub fn new(block_size: u64, depth: usize) -> Self {
+        SizeHists {
+            block_size,
+            depth,
+            global: SizeHist::new(),
+            by_user: HashMap::new(),
+            by_dir: HashMap::new(),
+        }
+    }
+
+    pub fn record(&mut self, fi: &FileInfo) {
+        let size = fi.stat.size;
+        self.global.add(size, self.block_size);
+        match self.by_user.get_mut(&fi.user) {
+            Some(h) => h.add(size, self.block_size),
+            None => self.by_user.entry(fi.user.clone()).or_insert_with(SizeHist::new).add(size, self.block_size),
+        }
+        for d in 1..=self.depth {
+            match dir_at_depth(&fi.path, d) {
+                Some(dir) => self.by_dir.entry(dir).or_insert_with(SizeHist::new).add(size, self.block_size),
+                None => break,
+            }
+        }
+    }
+
+    pub fn report(&self, top_n: usize) {
+        println!("\nFile size distribution");
+        self.global.print("", self.block_size);
+
+        let mut users = self.by_user.iter().collect::<Vec<_>>();
+        users.sort_by_key(|(_, h)| std::cmp::Reverse(h.files));
+        println!("\nFile size distribution per user by file count");
+        for (u, h) in users.iter().take(top_n) {
+            println!("{}", u);
+            h.print("    ", self.block_size);
+        }
+
+        let mut dirs = self.by_dir.iter().collect::<Vec<_>>();
+        dirs.sort_by_key(|(_, h)| std::cmp::Reverse(h.files));
+        println!("\nFile size distribution of top directories to depth {} by file count", self.depth);
+        for (d, h) in dirs.iter().take(top_n) {
+            println!("{}", d.display());
+            h.print("    ", self.block_size);
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::OpenOptions,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use anyhow::{anyhow, Context, Result};
+
+use crate::{
+    calendar::Calendar,
+    cli::TimeBucket,
+    data::greek,
+};
+
+/// One line of the history file: `<listing date ms>\t<kind>\t<name>\t<bytes>\t<files>`
+/// where kind is total, dir or user.
+#[derive(Debug, Clone)]
+pub struct HistoryRow {
+    pub date: u64,
+    pub kind: String,
+    pub name: String,
+    pub bytes: u64,
+    pub files: u64,
+}
+
+/// appends the rows of one run, the file is created on first use
+pub fn append(path: &Path, rows: &[HistoryRow]) -> Result<()> {
+    let f = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("cannot open history file {}", path.display()))?;
+    let mut w = BufWriter::new(f);
+    for r in rows {
+        writeln!(w, "{}\t{}\t{}\t{}\t{}", r.date, r.kind, r.name, r.bytes, r.files)?;
+    }
+    w.flush()?;
+    Ok(())
+}
+
+pub fn load(path: &Path) -> Result<Vec<HistoryRow>> {
+    let text = std::fs::read_to_string(path).with_context(|| format!("cannot read history file {}", path.display()))?;
+    let mut rows = vec![];
+    for (i, line) in text.lines().enumerate().filter(|(_, l)| !l.trim().is_empty()) {
+        let f = line.split('\t').collect::<Vec<_>>();
+        let bad = || anyhow!("{}:{} is not a history line: {}", path.display(), i + 1, line);
+        if f.len() != 5 {
+            return Err(bad());
+        }
+        rows.push(HistoryRow {
+            date: f[0].parse().map_err(|_| bad())?,
+            kind: f[1].to_string(),
+            name: f[2].to_string(),
+            bytes: f[3].parse().map_err(|_| bad())?,
+            files: f[4].parse().map_err(|_| bad())?,
+        });
+    }
+    Ok(rows)
+}
+
+/// bytes per listing date for every (kind, name), a later run for the same date replaces an earlier one
+pub fn series(rows: &[HistoryRow]) -> HashMap<(&str, &str), BTreeMap<u64, u64>> {
+    let mut s: HashMap<(&str, &str), BTreeMap<u64, u64>> = HashMap::new();
+    for r in rows {
+        s.entry((r.kind.as_str(), r.name.as_str())).or_default().insert(r.date, r.bytes);
+    }
+    s
+}
+
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+fn sparkline(v: &[u64]) -> String {
+    let (lo, hi) = (*v.iter().min().unwrap_or(&0), *v.iter().max().unwrap_or(&0));
+    v.iter()
+        .map(|x| if hi == lo { SPARKS[3] } else { SPARKS[((x - lo) * 7 / (hi - lo)) as usize] })
+        .collect()
+}
+
+pub fn signed_greek(d: i128) -> String {
+    format!("{}{}", if d < 0 { "-" } else { "+" }, greek(d.unsigned_abs() as f64))
+}
+
+/// deltas between the recorded weeks of `w`, the last `n` oldest first; a delta over missing weeks
+/// is labelled with all the weeks it covers
+fn week_deltas(cal: &Calendar, w: &[(u64, u64)], n: usize) -> Vec<String> {
+    let mut deltas = w
+        .windows(2)
+        .rev()
+        .take(n)
+        .map(|p| {
+            let (from, to) = (cal.bucket_end(p[0].0), p[1].0);
+            let label = if from == to {
+                cal.bucket_label(to)
+            } else {
+                format!("{}..{}", cal.bucket_label(from), cal.bucket_label(to))
+            };
+            format!("{} {}", label, signed_greek(p[1].1 as i128 - p[0].1 as i128))
+        })
+        .collect::<Vec<_>>();
+    deltas.reverse();
+    deltas
+}
+
+/// bytes for every week from the first to the last recorded one, a missing week keeps the bytes of the week before
+fn fill_weeks(cal: &Calendar, w: &[(u64, u64)]) -> Vec<u64> {
+    let mut filled = vec![];
+    for p in w.windows(2) {
+        let mut week = p[0].0;
+        while week < p[1].0 {
+            filled.push(p[0].1);
+            week = cal.bucket_end(week);
+        }
+    }
+    filled.extend(w.last().map(|(_, b)| *b));
+    filled
+}
+
+/// Growth curves and week over week deltas for the total and the top directories and users of the latest run.
+pub fn trend(path: &Path, tz_offset: i64, top_n: usize) -> Result<()> {
+    let rows = load(path)?;
+    let series = series(&rows);
+    let cal = Calendar::new(TimeBucket::Week, tz_offset);
+    let runs = rows.iter().map(|r| r.date).collect::<std::collections::BTreeSet<_>>();
+    let (first, last) = match (runs.iter().next(), runs.iter().last()) {
+        (Some(f), Some(l)) => (*f, *l),
+        _ => return Err(anyhow!("history file {} has no runs yet", path.display())),
+    };
+    println!(
+        "Trend from {} runs between {} and {} in {}",
+        runs.len(),
+        cal.fmt_date(first),
+        cal.fmt_date(last),
+        path.display()
+    );
+
+    // the last run of every week
+    let weekly = |s: &BTreeMap<u64, u64>| {
+        let mut w: BTreeMap<u64, u64> = BTreeMap::new();
+        for (d, b) in s {
+            w.insert(cal.bucket_start(*d), *b);
+        }
+        w
+    };
+
+    for kind in ["total", "dir", "user"] {
+        let mut latest = series
+            .iter()
+            .filter(|((k, _), _)| *k == kind)
+            .filter_map(|((_, n), s)| s.get(&last).map(|b| (*n, s, *b)))
+            .collect::<Vec<_>>();
+        latest.sort_by_key(|(_, _, b)| std::cmp::Reverse(*b));
+        if latest.is_empty() {
+            continue;
+        }
+        println!("\nTrend by {} - latest size, the last 4 deltas between recorded weeks (oldest first), weekly curve", kind);
+        for (name, s, bytes) in latest.iter().take(top_n) {
+            let w = weekly(s).into_iter().collect::<Vec<_>>();
+            let deltas = week_deltas(&cal, &w, 4);
+            let curve = sparkline(&fill_weeks(&cal, &w));
+            println!("{} {} [{}] {}", greek(*bytes as f64), name, deltas.join(", "), curve);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::{days_from_civil, DAY_MS};
+
+    fn week(y: i64, m: u32, d: u32) -> u64 {
+        (days_from_civil(y, m, d) * DAY_MS) as u64
+    }
+
+    #[test]
+    fn deltas_over_missing_weeks() {
+        let cal = Calendar::new(TimeBucket::Week, 0);
+        // 2026-W38, W39 and W42, W40 and W41 have no run
+        let w = [(week(2026, 9, 14), 1000), (week(2026, 9, 21), 1500), (week(2026, 10, 12), 1200)];
+        assert_eq!(week_deltas(&cal, &w, 4), ["2026-W39 +500  B", "2026-W40..2026-W42 -300  B"]);
+        assert_eq!(week_deltas(&cal, &w, 1), ["2026-W40..2026-W42 -300  B"]);
+        assert_eq!(fill_weeks(&cal, &w), [1000, 1500, 1500, 1500, 1200]);
+    }
+
+    #[test]
+    fn single_week() {
+        let cal = Calendar::new(TimeBucket::Week, 0);
+        let w = [(week(2026, 9, 14), 1000)];
+        assert!(week_deltas(&cal, &w, 4).is_empty());
+        assert_eq!(fill_weeks(&cal, &w), [1000]);
+    }
+}
@@ -0,0 +1,130 @@
+use std::{
+    cmp::max,
+    collections::HashMap,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use crate::{
+    data::{get_age, greek, FileInfo},
+    pattern::path_names,
+};
+
+struct Leftover {
+    kind: &'static str,
+    owner: String,
+    bytes: u64,
+    files: u64,
+    newest: u64,
+}
+
+impl Leftover {
+    fn new(kind: &'static str, owner: &str) -> Self {
+        Leftover {
+            kind,
+            owner: owner.to_string(),
+            bytes: 0,
+            files: 0,
+            newest: 0,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Output {
+    owner: String,
+    parts: bool,
+    success: bool,
+    bytes: u64,
+    files: u64,
+    newest: u64,
+}
+
+/// Directories failed or killed jobs leave behind: _temporary, .hive-staging_* and .spark-staging*
+/// directories, and output directories with part-* files but no _SUCCESS marker.
+pub struct Leftovers {
+    temp: HashMap<PathBuf, Leftover>,
+    outputs: HashMap<PathBuf, Output>,
+}
+
+fn temp_kind(name: &str) -> Option<&'static str> {
+    if name == "_temporary" {
+        Some("_temporary")
+    } else if name.starts_with(".hive-staging") {
+        Some("hive-staging")
+    } else if name.starts_with(".spark-staging") {
+        Some("spark-staging")
+    } else {
+        None
+    }
+}
+
+/// the highest temporary directory in a path and its kind
+fn temp_dir(path: &Path) -> Option<(PathBuf, &'static str)> {
+    let names = path_names(path);
+    let (i, kind) = names.iter().enumerate().find_map(|(i, n)| temp_kind(n).map(|k| (i, k)))?;
+    let mut dir = PathBuf::from("/");
+    dir.extend(&names[..=i]);
+    Some((dir, kind))
+}
+
+impl Leftovers {
+    pub fn new() -> Self {
+        Leftovers {
+            temp: HashMap::new(),
+            outputs: HashMap::new(),
+        }
+    }
+
+    pub fn record(&mut self, fi: &FileInfo) {
+        if let Some((dir, kind)) = temp_dir(&fi.path) {
+            // the first entry seen is normally the directory itself, so it names the owner
+            let t = self.temp.entry(dir).or_insert_with(|| Leftover::new(kind, &fi.user));
+            if !fi.is_dir() {
+                t.bytes += fi.stat.size;
+                t.files += 1;
+                t.newest = max(t.newest, fi.stat.mod_time);
+            }
+            return;
+        }
+        if fi.is_dir() {
+            return;
+        }
+        let (parent, name) = match (fi.path.parent(), fi.path.file_name().and_then(|n| n.to_str())) {
+            (Some(p), Some(n)) => (p, n),
+            _ => return,
+        };
+        let o = match self.outputs.get_mut(parent) {
+            Some(o) => o,
+            None => self.outputs.entry(parent.to_path_buf()).or_default(),
+        };
+        if o.files == 0 {
+            o.owner = fi.user.clone();
+        }
+        o.parts |= name.starts_with("part-");
+        o.success |= name == "_SUCCESS";
+        o.bytes += fi.stat.size;
+        o.files += 1;
+        o.newest = max(o.newest, fi.stat.mod_time);
+    }
+
+    pub fn report(&self, top_n: usize) {
+        let now = SystemTime::now();
+        let mut all = self.temp.iter().map(|(p, t)| (p, t.kind, &t.owner, t.bytes, t.files, t.newest)).collect::<Vec<_>>();
+        all.extend(
+            self.outputs
+                .iter()
+                .filter(|(_, o)| o.parts && !o.success)
+                .map(|(p, o)| (p, "no _SUCCESS", &o.owner, o.bytes, o.files, o.newest)),
+        );
+        all.sort_by_key(|l| std::cmp::Reverse(l.3));
+
+        let bytes = all.iter().map(|l| l.3).sum::<u64>();
+        println!("\nLeftover temporary and staging directories - {} in {} directories", greek(bytes as f64), all.len());
+        println!("{:>9} {:>8} {:>12} {:>9} {:>10} directory", "size", "files", "kind", "age", "owner");
+        for (p, kind, owner, bytes, files, newest) in all.iter().take(top_n) {
+            let age = if *newest > 0 { get_age(now, *newest) } else { "-".to_string() };
+            println!("{} {:8} {:>12} {:>9} {:>10} {}", greek(*bytes as f64), files, kind, age, owner, p.display());
+        }
+    }
+}
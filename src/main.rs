@@ -3,15 +3,40 @@
 use std::{collections::{self, BTreeMap}, fs::File, io::{BufReader, Read}, path::PathBuf, str::FromStr, time::{Duration, Instant}};
 
 use crate::{
-    cli::CliCfg,
+    cli::{CliCfg, InputFormat},
     data::{dur_to_str, uri_to_path, FileInfo, Tracking},
 };
 use csv::{StringRecord, StringRecordsIter};
 use flate2::bufread::GzDecoder;
 use structopt::StructOpt;
 
+mod age;
+mod anomaly;
+mod calendar;
+mod chargeback;
+mod classify;
+mod cleanup;
 mod cli;
+mod cold;
 mod data;
+mod dirmeta;
+mod forecast;
+mod hist;
+mod history;
+mod leftover;
+mod namenode;
+mod owner;
+mod partition;
+mod pathdate;
+mod pattern;
+mod recent;
+mod retention;
+mod smallfiles;
+mod snapshot;
+mod stale;
+mod template;
+mod trash;
+mod xtab;
 
 use anyhow::{anyhow, Context, Error, Result};
 
@@ -26,6 +51,11 @@ fn main() -> Result<()> {
 
     let cfg: CliCfg = CliCfg::from_args();
 
+    if cfg.trend {
+        let history = cfg.history.as_ref().ok_or_else(|| anyhow!("--trend needs the --history file to report from"))?;
+        return history::trend(history, cfg.tz_offset, cfg.top_n);
+    }
+
     let mut bld = csv::ReaderBuilder::new();
 
     let csv_vbld = bld
@@ -78,18 +108,27 @@ fn main() -> Result<()> {
     for i in 0..cfg.num_rec_threads {
         let recv_sr = recv_sr.clone();
         let send_fi = send_fi.clone();
+        let (input_format, tz_offset) = (cfg.input_format, cfg.tz_offset);
 
         ct.push(std::thread::spawn(move || loop {
             match recv_sr.recv() {
                 Ok(sr) => match sr {
-                    Some((line, sr)) => match FileInfo::new(sr) {
-                        Ok(fi) => {
-                            send_fi
-                                .send(Some((line, fi)))
-                                .expect("Unable to send string record");
+                    // the oiv header line
+                    Some((1, sr)) if input_format == InputFormat::Oiv && sr.get(0) == Some("Path") => {}
+                    Some((line, sr)) => {
+                        let fi = match input_format {
+                            InputFormat::Native => FileInfo::new(sr),
+                            InputFormat::Oiv => FileInfo::from_oiv(sr, tz_offset),
+                        };
+                        match fi {
+                            Ok(fi) => {
+                                send_fi
+                                    .send(Some((line, fi)))
+                                    .expect("Unable to send string record");
+                            }
+                            Err(e) => println!("error csv rec line {} due to {}", line, e),
                         }
-                        Err(e) => println!("error csv rec line {} due to {}", line, e),
-                    },
+                    }
                     None => return,
                 },
                 Err(e) => eprintln!("cannot recv due to {}", e),
@@ -98,8 +137,8 @@ fn main() -> Result<()> {
     }
 
     let cfg_c = cfg.clone();
+    let mut data = Tracking::new(&cfg)?;
     let data_thread = std::thread::spawn(move || {
-        let mut data = Tracking::new();
         loop {
             match recv_fi.recv() {
                 Ok(msg) => match msg {
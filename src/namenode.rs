@@ -0,0 +1,111 @@
+use std::{
+    collections::HashMap,
+    path::{Component, Path, PathBuf},
+};
+
+use crate::data::{greek, top_dir, DirStat, FileInfo};
+
+#[derive(Default, Clone, Copy)]
+struct Objects {
+    inodes: u64,
+    blocks: u64,
+}
+
+/// Estimated NameNode objects and heap - an inode for every entry plus its blocks.
+pub struct NameNode {
+    inode_bytes: u64,
+    block_bytes: u64,
+    total: Objects,
+    by_user: HashMap<String, Objects>,
+    by_top_dir: HashMap<PathBuf, Objects>,
+}
+
+impl NameNode {
+    pub fn new(inode_bytes: u64, block_bytes: u64) -> Self {
+        NameNode {
+            inode_bytes,
+            block_bytes,
+            total: Objects::default(),
+            by_user: HashMap::new(),
+            by_top_dir: HashMap::new(),
+        }
+    }
+
+    fn heap(&self, o: &Objects) -> u64 {
+        o.inodes * self.inode_bytes + o.blocks * self.block_bytes
+    }
+
+    pub fn record(&mut self, fi: &FileInfo) {
+        let top = if fi.is_dir() && fi.path.components().count() == 2 {
+            fi.path.as_path()
+        } else {
+            top_dir(&fi.path).unwrap_or(Path::new("/"))
+        };
+        let user = match self.by_user.get_mut(&fi.user) {
+            Some(o) => o,
+            None => self.by_user.entry(fi.user.clone()).or_default(),
+        };
+        let dir = match self.by_top_dir.get_mut(top) {
+            Some(o) => o,
+            None => self.by_top_dir.entry(top.to_path_buf()).or_default(),
+        };
+        for o in [&mut self.total, user, dir] {
+            o.inodes += 1;
+            o.blocks += fi.stat.blocks;
+        }
+    }
+
+    fn print(&self, name: &str, o: &Objects) {
+        println!("{:10} {:10} {:10} {} {}", o.inodes + o.blocks, o.inodes, o.blocks, greek(self.heap(o) as f64), name);
+    }
+
+    pub fn report(&self, dtree: &HashMap<PathBuf, DirStat>, top_n: usize) {
+        println!(
+            "\nNameNode footprint estimate at {} bytes per inode and {} bytes per block",
+            self.inode_bytes, self.block_bytes
+        );
+        println!("{:>10} {:>10} {:>10} {:9}", "objects", "inodes", "blocks", "heap");
+        self.print("total", &self.total);
+
+        let sorted = |m: &HashMap<String, Objects>| {
+            let mut v = m.iter().map(|(n, o)| (n.clone(), *o)).collect::<Vec<_>>();
+            v.sort_by_key(|(_, o)| std::cmp::Reverse(o.inodes + o.blocks));
+            v
+        };
+
+        println!("\nNameNode objects by top level directory");
+        let tops = self.by_top_dir.iter().map(|(p, o)| (p.to_string_lossy().to_string(), *o)).collect();
+        for (n, o) in sorted(&tops).iter().take(top_n) {
+            self.print(n, o);
+        }
+
+        println!("\nNameNode objects by user");
+        for (n, o) in sorted(&self.by_user).iter().take(top_n) {
+            self.print(n, o);
+        }
+
+        // directories are not rolled up into their parents so count them per subtree here
+        let mut sub_dirs: HashMap<&std::path::Path, u64> = HashMap::new();
+        for p in dtree.keys() {
+            for a in p.ancestors().skip(1) {
+                *sub_dirs.entry(a).or_default() += 1;
+            }
+        }
+        let mut subtrees = dtree
+            .iter()
+            .filter(|(p, _)| p.components().any(|c| matches!(c, Component::Normal(_))))
+            .map(|(p, s)| {
+                let o = Objects {
+                    inodes: s.recurse.entry_cnt + sub_dirs.get(p.as_path()).copied().unwrap_or(0),
+                    blocks: s.recurse.blocks,
+                };
+                (p.to_string_lossy().to_string(), o)
+            })
+            .collect::<Vec<_>>();
+        subtrees.sort_by_key(|(_, o)| std::cmp::Reverse(o.inodes + o.blocks));
+        println!("\nSubtrees contributing the most NameNode objects");
+        for (n, o) in subtrees.iter().take(top_n) {
+            self.print(n, o);
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use std::{
+    collections::{BinaryHeap, HashMap},
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use anyhow::{Context, Result};
+
+use crate::{
+    age::{age_class, AGE_CLASSES, AGE_CLASS_CNT},
+    data::{get_age, greek, pct, to_sort_vec, track_top_n, FileInfo, Tracked},
+};
+
+/// What one owner holds: totals, their largest files and the directories holding most of their bytes directly.
+pub struct OwnerStats {
+    pub files: u64,
+    pub bytes: u64,
+    pub largest_files: BinaryHeap<Tracked<PathBuf>>,
+    pub dirs: HashMap<PathBuf, (u64, u64)>,
+    pub ages: [(u64, u64); AGE_CLASS_CNT],
+}
+
+impl OwnerStats {
+    fn new() -> Self {
+        OwnerStats {
+            files: 0,
+            bytes: 0,
+            largest_files: BinaryHeap::new(),
+            dirs: HashMap::new(),
+            ages: [(0, 0); AGE_CLASS_CNT],
+        }
+    }
+
+    /// directories sorted by the bytes this owner has directly in them, largest first
+    pub fn top_dirs(&self, limit: usize) -> Vec<(&PathBuf, (u64, u64))> {
+        let mut v = self.dirs.iter().map(|(d, c)| (d, *c)).collect::<Vec<_>>();
+        v.sort_by_key(|e| std::cmp::Reverse(e.1 .0));
+        v.truncate(limit);
+        v
+    }
+}
+
+/// Per owner stats kept for a per-user cleanup view of the same run.
+pub struct PerOwner {
+    limit: usize,
+    now_ms: u64,
+    pub owners: HashMap<String, OwnerStats>,
+}
+
+impl PerOwner {
+    pub fn new(limit: usize, now_ms: u64) -> Self {
+        PerOwner {
+            limit,
+            now_ms,
+            owners: HashMap::new(),
+        }
+    }
+
+    pub fn record(&mut self, owner: &str, fi: &FileInfo) {
+        let os = match self.owners.get_mut(owner) {
+            Some(os) => os,
+            None => self.owners.entry(owner.to_string()).or_insert_with(OwnerStats::new),
+        };
+        os.files += 1;
+        os.bytes += fi.stat.size;
+        let age = &mut os.ages[age_class(self.now_ms, fi.stat.mod_time)];
+        age.0 += fi.stat.size;
+        age.1 += 1;
+        track_top_n(&mut os.largest_files, &fi.path, fi.stat.size, self.limit, fi.stat.mod_time, 0);
+        if let Some(parent) = fi.path.parent() {
+            let d = match os.dirs.get_mut(parent) {
+                Some(d) => d,
+                None => os.dirs.entry(parent.to_path_buf()).or_insert((0, 0)),
+            };
+            d.0 += fi.stat.size;
+            d.1 += 1;
+        }
+    }
+
+    /// owners sorted by bytes, largest first
+    pub fn sorted(&self) -> Vec<(&String, &OwnerStats)> {
+        let mut v = self.owners.iter().collect::<Vec<_>>();
+        v.sort_by_key(|e| std::cmp::Reverse(e.1.bytes));
+        v
+    }
+
+    pub fn report(&self, top_n: usize) {
+        let now = SystemTime::now();
+        println!("\nLargest files and directories per user");
+        for (owner, os) in self.sorted().iter().take(top_n) {
+            println!("{} {:8} {}", greek(os.bytes as f64), os.files, owner);
+            let ages = AGE_CLASSES.iter().zip(os.ages.iter()).map(|((n, _), a)| format!("{}: {}", n, greek(a.0 as f64))).collect::<Vec<_>>();
+            println!("  age [{}]", ages.join(", "));
+            println!("  largest files");
+            for tp in to_sort_vec(&os.largest_files) {
+                println!("    {} {}  age:[{}]", greek(tp.size as f64), tp.track.display(), get_age(now, tp.old));
+            }
+            println!("  top directories by owned bytes");
+            for (d, c) in os.top_dirs(self.limit) {
+                println!("    {} {:8} {}", greek(c.0 as f64), c.1, d.display());
+            }
+        }
+    }
+
+    /// one report file per owner named after the owner, `total_*` are the cluster file and byte totals used for the share
+    pub fn write_reports(&self, dir: &Path, total_files: u64, total_bytes: u64) -> Result<()> {
+        std::fs::create_dir_all(dir).with_context(|| format!("cannot create report directory {}", dir.display()))?;
+        let now = SystemTime::now();
+        for (owner, os) in &self.owners {
+            let name = owner.replace(|c: char| c == '/' || c == '\\' || c.is_whitespace(), "_");
+            let path = dir.join(format!("{}.txt", name));
+            let mut w = BufWriter::new(File::create(&path).with_context(|| format!("cannot create report {}", path.display()))?);
+
+            writeln!(w, "Storage report for {}", owner)?;
+            writeln!(w, "\nTotals")?;
+            writeln!(w, "{} in {} files", greek(os.bytes as f64), os.files)?;
+            writeln!(w, "{:.2}% of cluster space, {:.2}% of cluster files", pct(os.bytes, total_bytes), pct(os.files, total_files))?;
+
+            writeln!(w, "\nAge profile by modification time")?;
+            for ((n, _), a) in AGE_CLASSES.iter().zip(os.ages.iter()) {
+                writeln!(w, "{:>8} {} {:8} {:5.1}%", n, greek(a.0 as f64), a.1, pct(a.0, os.bytes))?;
+            }
+
+            writeln!(w, "\nLargest files")?;
+            for tp in to_sort_vec(&os.largest_files) {
+                writeln!(w, "{} {}  age:[{}]", greek(tp.size as f64), tp.track.display(), get_age(now, tp.old))?;
+            }
+
+            writeln!(w, "\nBiggest directories by bytes you own directly in them")?;
+            for (d, c) in os.top_dirs(self.limit) {
+                writeln!(w, "{} {:8} {}", greek(c.0 as f64), c.1, d.display())?;
+            }
+            w.flush()?;
+        }
+        Ok(())
+    }
+}
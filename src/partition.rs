@@ -0,0 +1,75 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+};
+
+use crate::{
+    data::{greek, FileInfo},
+    pattern::path_names,
+};
+
+#[derive(Default)]
+struct Table {
+    bytes: u64,
+    files: u64,
+    /// files per partition like dt=2021-07-01/hour=05, sorted so the ends are the oldest and newest
+    partitions: BTreeMap<String, u64>,
+}
+
+/// Hive style tables: the directory above the first `key=value` component of a path,
+/// with every distinct run of `key=value` components below it as one partition.
+pub struct Partitions {
+    tables: HashMap<PathBuf, Table>,
+}
+
+fn is_partition(name: &str) -> bool {
+    matches!(name.find('='), Some(i) if i > 0)
+}
+
+impl Partitions {
+    pub fn new() -> Self {
+        Partitions { tables: HashMap::new() }
+    }
+
+    pub fn record(&mut self, fi: &FileInfo) {
+        let names = path_names(&fi.path);
+        // the file name itself is never a partition
+        let dirs = &names[..names.len().saturating_sub(1)];
+        let first = match dirs.iter().position(|n| is_partition(n)) {
+            Some(i) => i,
+            None => return,
+        };
+        let spec = dirs[first..].iter().take_while(|n| is_partition(n)).copied().collect::<Vec<_>>().join("/");
+        let mut table = PathBuf::from("/");
+        table.extend(&dirs[..first]);
+        let t = self.tables.entry(table).or_default();
+        t.bytes += fi.stat.size;
+        t.files += 1;
+        *t.partitions.entry(spec).or_default() += 1;
+    }
+
+    pub fn report(&self, top_n: usize) {
+        let mut tables = self.tables.iter().collect::<Vec<_>>();
+        tables.sort_by_key(|(_, t)| std::cmp::Reverse(t.bytes));
+        println!(
+            "\nPartitioned tables - {} tables in {} partitions",
+            tables.len(),
+            tables.iter().map(|(_, t)| t.partitions.len()).sum::<usize>()
+        );
+        println!("{:>9} {:>8} {:>6} {:>10} table [oldest .. newest partition]", "size", "files", "parts", "files/part");
+        for (p, t) in tables.iter().take(top_n) {
+            let first = t.partitions.keys().next().map_or("", |k| k.as_str());
+            let last = t.partitions.keys().next_back().map_or("", |k| k.as_str());
+            println!(
+                "{} {:8} {:6} {:10.1} {} [{} .. {}]",
+                greek(t.bytes as f64),
+                t.files,
+                t.partitions.len(),
+                t.files as f64 / t.partitions.len() as f64,
+                p.display(),
+                first,
+                last
+            );
+        }
+    }
+}
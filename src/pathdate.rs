@@ -0,0 +1,165 @@
+use std::{collections::BTreeMap, path::Path};
+
+use anyhow::{anyhow, Result};
+
+use crate::{
+    calendar::{civil_from_days, days_from_civil, Calendar},
+    data::{greek, FileInfo},
+    pattern::PathPattern,
+};
+
+/// Path pattern with the placeholders {yyyy}, {mm} and {dd} marking where the logical
+/// date of the data sits, e.g. /logs/{yyyy}/{mm}/{dd}/** or /**/dt={yyyy}-{mm}-{dd}/**.
+pub struct DatePattern(PathPattern);
+
+impl DatePattern {
+    pub fn new(text: &str) -> Result<Self> {
+        let p = PathPattern::new(text).map_err(|_| anyhow!("path date pattern \"{}\" must start with /", text))?;
+        if !p.has_placeholder("{yyyy}") {
+            return Err(anyhow!("path date pattern \"{}\" needs at least a {{yyyy}}", text));
+        }
+        Ok(DatePattern(p))
+    }
+
+    /// logical date of the path as days since the epoch, a missing month or day is the first
+    pub fn date_days(&self, path: &Path) -> Option<i64> {
+        let (mut y, mut m, mut d) = (0, 1, 1);
+        for (ph, v) in self.0.captures(path)? {
+            match ph {
+                "{yyyy}" => y = v.parse().ok()?,
+                "{mm}" => m = v.parse().ok()?,
+                _ => d = v.parse().ok()?,
+            }
+        }
+        if !(1..=12).contains(&m) || !(1..=31).contains(&d) {
+            return None;
+        }
+        Some(days_from_civil(y, m, d))
+    }
+}
+
+#[derive(Default, Clone, Copy)]
+struct Month {
+    by_path: u64,
+    by_mtime: u64,
+}
+
+/// Logical dates taken from paths set against mod times, per month and for a retention cutoff.
+pub struct PathDates {
+    patterns: Vec<DatePattern>,
+    cutoff_days: Option<i64>,
+    months: BTreeMap<(i64, u32), Month>,
+    dated: (u64, u64),
+    undated: (u64, u64),
+    /// bytes whose path month differs from their mtime month, like after a backfill
+    shifted: u64,
+    reclaim_by_path: (u64, u64),
+    reclaim_by_mtime: (u64, u64),
+}
+
+impl PathDates {
+    /// the cutoff is in local days of `cal` like the mod times it is compared with
+    pub fn new(patterns: &[String], max_age_ms: Option<u64>, now_ms: u64, cal: &Calendar) -> Result<Self> {
+        Ok(PathDates {
+            patterns: patterns.iter().map(|p| DatePattern::new(p)).collect::<Result<Vec<_>>>()?,
+            cutoff_days: max_age_ms.map(|a| cal.local_days(now_ms.saturating_sub(a))),
+            months: BTreeMap::new(),
+            dated: (0, 0),
+            undated: (0, 0),
+            shifted: 0,
+            reclaim_by_path: (0, 0),
+            reclaim_by_mtime: (0, 0),
+        })
+    }
+
+    pub fn record(&mut self, fi: &FileInfo, cal: &Calendar) {
+        let size = fi.stat.size;
+        let days = match self.patterns.iter().find_map(|p| p.date_days(&fi.path)) {
+            Some(d) => d,
+            None => {
+                self.undated.0 += size;
+                self.undated.1 += 1;
+                return;
+            }
+        };
+        self.dated.0 += size;
+        self.dated.1 += 1;
+        let mtime_days = cal.local_days(fi.stat.mod_time);
+        let (py, pm, _) = civil_from_days(days);
+        let (my, mm, _) = civil_from_days(mtime_days);
+        self.months.entry((py, pm)).or_default().by_path += size;
+        self.months.entry((my, mm)).or_default().by_mtime += size;
+        if (py, pm) != (my, mm) {
+            self.shifted += size;
+        }
+        if let Some(cutoff) = self.cutoff_days {
+            if days < cutoff {
+                self.reclaim_by_path.0 += size;
+                self.reclaim_by_path.1 += 1;
+            }
+            if mtime_days < cutoff {
+                self.reclaim_by_mtime.0 += size;
+                self.reclaim_by_mtime.1 += 1;
+            }
+        }
+    }
+
+    pub fn report(&self) {
+        println!(
+            "\nPath dates - {} in {} files dated by {}, {} in {} files without a path date",
+            greek(self.dated.0 as f64),
+            self.dated.1,
+            self.patterns.iter().map(|p| p.0.text.as_str()).collect::<Vec<_>>().join(" "),
+            greek(self.undated.0 as f64),
+            self.undated.1
+        );
+        println!("{} of dated bytes fall in a different month by path than by mod time", greek(self.shifted as f64));
+        println!("{:>7} {:>9} {:>9}", "month", "by path", "by mtime");
+        for ((y, m), c) in &self.months {
+            println!("{:04}-{:02} {} {}", y, m, greek(c.by_path as f64), greek(c.by_mtime as f64));
+        }
+        if let Some(cutoff) = self.cutoff_days {
+            let (y, m, d) = civil_from_days(cutoff);
+            println!(
+                "\nReclaimable dated data before {:04}-{:02}-{:02} - {} in {} files by path date, {} in {} files by mod time",
+                y,
+                m,
+                d,
+                greek(self.reclaim_by_path.0 as f64),
+                self.reclaim_by_path.1,
+                greek(self.reclaim_by_mtime.0 as f64),
+                self.reclaim_by_mtime.1
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{calendar::DAY_MS, cli::TimeBucket};
+
+    fn days(pat: &str, path: &str) -> Option<(i64, u32, u32)> {
+        DatePattern::new(pat).unwrap().date_days(Path::new(path)).map(civil_from_days)
+    }
+
+    #[test]
+    fn dates_from_paths() {
+        assert_eq!(days("/logs/{yyyy}/{mm}/{dd}/**", "/logs/2026/07/04/f"), Some((2026, 7, 4)));
+        assert_eq!(days("/**/dt={yyyy}-{mm}/**", "/w/t/dt=2026-07/f"), Some((2026, 7, 1)));
+        assert_eq!(days("/logs/{yyyy}/**", "/logs/2026/f"), Some((2026, 1, 1)));
+        assert_eq!(days("/logs/{yyyy}/{mm}/**", "/logs/2026/13/f"), None);
+        assert_eq!(days("/logs/{yyyy}/**", "/data/2026/f"), None);
+        assert!(DatePattern::new("/logs/{mm}/**").is_err());
+        assert!(DatePattern::new("logs/{yyyy}").is_err());
+    }
+
+    #[test]
+    fn cutoff_in_local_days() {
+        // 2026-10-18 02:00 UTC is still 2026-10-17 at -05:00
+        let now = (days_from_civil(2026, 10, 18) * DAY_MS + 2 * 3600 * 1000) as u64;
+        let cal = Calendar::new(TimeBucket::Day, -300);
+        let pd = PathDates::new(&["/l/{yyyy}/**".to_string()], Some(DAY_MS as u64), now, &cal).unwrap();
+        assert_eq!(pd.cutoff_days.map(civil_from_days), Some((2026, 10, 16)));
+    }
+}
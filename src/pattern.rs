@@ -0,0 +1,178 @@
+use std::path::{Component, Path};
+
+use anyhow::{anyhow, Result};
+
+/// Shell like path pattern matched a component at a time: `*` and `?` stay within a
+/// component and a `**` component matches any number of components, e.g. /data/*/logs/**.
+/// The placeholders {yyyy}, {mm} and {dd} match 4, 2 and 2 digits and are captured.
+#[derive(Debug, Clone)]
+pub struct PathPattern {
+    pub text: String,
+    parts: Vec<String>,
+}
+
+/// placeholders with the number of digits they match
+const PLACEHOLDERS: [(&str, usize); 3] = [("{yyyy}", 4), ("{mm}", 2), ("{dd}", 2)];
+
+/// placeholder and the text it matched
+pub type Captures<'a> = Vec<(&'static str, &'a str)>;
+
+fn glob_component<'a>(p: &[u8], s: &'a [u8], caps: &mut Captures<'a>) -> bool {
+    let mark = caps.len();
+    let hit = if let Some((ph, len)) = PLACEHOLDERS.iter().find(|(ph, _)| p.starts_with(ph.as_bytes())) {
+        s.len() >= *len && s[..*len].iter().all(u8::is_ascii_digit) && {
+            caps.push((ph, std::str::from_utf8(&s[..*len]).unwrap_or_default()));
+            glob_component(&p[ph.len()..], &s[*len..], caps)
+        }
+    } else {
+        match p.first() {
+            None => s.is_empty(),
+            Some(b'*') => (0..=s.len()).any(|i| glob_component(&p[1..], &s[i..], caps)),
+            Some(b'?') => !s.is_empty() && glob_component(&p[1..], &s[1..], caps),
+            Some(c) => s.first() == Some(c) && glob_component(&p[1..], &s[1..], caps),
+        }
+    };
+    // captures of a failed attempt must not leak into the next one
+    if !hit {
+        caps.truncate(mark);
+    }
+    hit
+}
+
+fn match_parts<'a>(pat: &[String], comps: &[&'a str], caps: &mut Captures<'a>) -> bool {
+    let mark = caps.len();
+    let hit = match pat.first() {
+        None => comps.is_empty(),
+        Some(p) if p == "**" => (0..=comps.len()).any(|i| match_parts(&pat[1..], &comps[i..], caps)),
+        Some(p) => {
+            !comps.is_empty() && glob_component(p.as_bytes(), comps[0].as_bytes(), caps) && match_parts(&pat[1..], &comps[1..], caps)
+        }
+    };
+    if !hit {
+        caps.truncate(mark);
+    }
+    hit
+}
+
+/// the names in a path without the root
+pub fn path_names(path: &Path) -> Vec<&str> {
+    path.components()
+        .filter_map(|c| match c {
+            Component::Normal(n) => n.to_str(),
+            _ => None,
+        })
+        .collect()
+}
+
+impl PathPattern {
+    pub fn new(text: &str) -> Result<Self> {
+        if !text.starts_with('/') {
+            return Err(anyhow!("path pattern \"{}\" must start with /", text));
+        }
+        Ok(PathPattern {
+            text: text.to_string(),
+            parts: text.split('/').filter(|p| !p.is_empty()).map(|p| p.to_string()).collect(),
+        })
+    }
+
+    /// number of leading components without wildcards or placeholders, e.g. 2 for /user/bob/**/tmp
+    pub fn literal_depth(&self) -> usize {
+        self.parts
+            .iter()
+            .take_while(|p| !p.contains(['*', '?']) && !PLACEHOLDERS.iter().any(|(ph, _)| p.contains(ph)))
+            .count()
+    }
+
+    pub fn has_placeholder(&self, ph: &str) -> bool {
+        self.parts.iter().any(|p| p.contains(ph))
+    }
+
+    pub fn matches(&self, path: &Path) -> bool {
+        self.captures(path).is_some()
+    }
+
+    /// what the placeholders matched in order, None when the path does not match
+    pub fn captures<'a>(&self, path: &'a Path) -> Option<Captures<'a>> {
+        let mut caps = vec![];
+        match_parts(&self.parts, &path_names(path), &mut caps).then_some(caps)
+    }
+}
+
+/// `<pattern> <value>` lines of a rules file with their line numbers, blank lines and # comments are skipped
+pub fn read_rules(path: &Path) -> Result<Vec<(usize, PathPattern, String)>> {
+    let text = std::fs::read_to_string(path).map_err(|e| anyhow!("cannot read rules file {}: {}", path.display(), e))?;
+    let mut rules = vec![];
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (pat, rest) = line.split_at(line.find(char::is_whitespace).unwrap_or(line.len()));
+        if rest.trim().is_empty() {
+            return Err(anyhow!("{}:{} rule \"{}\" has no value after the pattern", path.display(), i + 1, line));
+        }
+        let pat = PathPattern::new(pat).map_err(|e| anyhow!("{}:{} {}", path.display(), i + 1, e))?;
+        rules.push((i + 1, pat, rest.trim().to_string()));
+    }
+    Ok(rules)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn m(pat: &str, path: &str) -> bool {
+        PathPattern::new(pat).unwrap().matches(Path::new(path))
+    }
+
+    #[test]
+    fn components() {
+        assert!(m("/data/*/logs", "/data/x/logs"));
+        assert!(!m("/data/*/logs", "/data/x/y/logs"));
+        assert!(m("/tmp/job_?", "/tmp/job_1"));
+        assert!(!m("/tmp/job_?", "/tmp/job_12"));
+        assert!(m("/a/*.gz", "/a/log.1.gz"));
+        assert!(!m("/a/*.gz", "/a/b/log.gz"));
+        assert!(!m("/a", "/a/b"));
+    }
+
+    #[test]
+    fn double_star() {
+        assert!(m("/tmp/**", "/tmp/a/b/c"));
+        // ** matches no components too
+        assert!(m("/tmp/**", "/tmp"));
+        assert!(m("/user/*/.Trash/**", "/user/bob/.Trash/Current/f"));
+        assert!(!m("/user/*/.Trash/**", "/user/bob/x/.Trash/f"));
+        assert!(m("/**/_temporary/**", "/out/_temporary/0/part-0"));
+        assert!(m("/**/*.orc", "/w/t/dt=1/f.orc"));
+        assert!(!m("/**/*.orc", "/w/t/dt=1/f.parquet"));
+        assert!(m("/a/**/b/**/c", "/a/x/b/y/z/c"));
+    }
+
+    #[test]
+    fn literal_depth() {
+        assert_eq!(PathPattern::new("/tmp/**").unwrap().literal_depth(), 1);
+        assert_eq!(PathPattern::new("/user/bob/**/tmp").unwrap().literal_depth(), 2);
+        assert_eq!(PathPattern::new("/**/_temporary").unwrap().literal_depth(), 0);
+        assert_eq!(PathPattern::new("/data/x").unwrap().literal_depth(), 2);
+    }
+
+    #[test]
+    fn placeholders() {
+        let p = PathPattern::new("/**/dt={yyyy}-{mm}-{dd}/**").unwrap();
+        assert_eq!(p.captures(Path::new("/w/t/dt=2026-10-18/f")), Some(vec![("{yyyy}", "2026"), ("{mm}", "10"), ("{dd}", "18")]));
+        assert_eq!(p.captures(Path::new("/w/t/dt=26-10-18/f")), None);
+        // a failed * attempt leaves no captures behind
+        let p = PathPattern::new("/logs/*{yyyy}/x").unwrap();
+        assert_eq!(p.captures(Path::new("/logs/a20261999/x")), Some(vec![("{yyyy}", "1999")]));
+        let p = PathPattern::new("/**/{yyyy}/{mm}").unwrap();
+        assert_eq!(p.captures(Path::new("/a/2025/b/2026/07")), Some(vec![("{yyyy}", "2026"), ("{mm}", "07")]));
+        assert_eq!(PathPattern::new("/logs/{yyyy}/**").unwrap().literal_depth(), 1);
+    }
+
+    #[test]
+    fn bad_patterns() {
+        assert!(PathPattern::new("tmp/**").is_err());
+        assert_eq!(path_names(Path::new("/a//b/")), ["a", "b"]);
+    }
+}
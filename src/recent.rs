@@ -0,0 +1,84 @@
+use std::{
+    collections::{BinaryHeap, HashMap},
+    path::PathBuf,
+    time::SystemTime,
+};
+
+use crate::data::{get_age, greek, to_sort_vec, track_top_n, FileInfo, Tracked};
+
+/// Where the writes of the last window landed: bytes and files with a mod time inside it
+/// per directory (recursively), per user and the largest of those files.
+pub struct Recent {
+    cutoff: u64,
+    limit: usize,
+    total: (u64, u64),
+    dirs: HashMap<PathBuf, (u64, u64)>,
+    users: HashMap<String, (u64, u64)>,
+    largest_file: BinaryHeap<Tracked<PathBuf>>,
+}
+
+impl Recent {
+    pub fn new(now_ms: u64, window_ms: u64, limit: usize) -> Self {
+        Recent {
+            cutoff: now_ms.saturating_sub(window_ms),
+            limit,
+            total: (0, 0),
+            dirs: HashMap::new(),
+            users: HashMap::new(),
+            largest_file: BinaryHeap::new(),
+        }
+    }
+
+    pub fn record(&mut self, fi: &FileInfo) {
+        if fi.stat.mod_time < self.cutoff {
+            return;
+        }
+        let size = fi.stat.size;
+        self.total.0 += size;
+        self.total.1 += 1;
+        for a in fi.path.ancestors().skip(1) {
+            let d = match self.dirs.get_mut(a) {
+                Some(d) => d,
+                None => self.dirs.entry(a.to_path_buf()).or_insert((0, 0)),
+            };
+            d.0 += size;
+            d.1 += 1;
+        }
+        let u = match self.users.get_mut(&fi.user) {
+            Some(u) => u,
+            None => self.users.entry(fi.user.clone()).or_insert((0, 0)),
+        };
+        u.0 += size;
+        u.1 += 1;
+        track_top_n(&mut self.largest_file, &fi.path, size, self.limit, fi.stat.mod_time, 0);
+    }
+
+    pub fn report(&self) {
+        let now = SystemTime::now();
+        println!(
+            "\nRecently written - {} in {} files since {} ago",
+            greek(self.total.0 as f64),
+            self.total.1,
+            get_age(now, self.cutoff)
+        );
+
+        let mut dirs = self.dirs.iter().collect::<Vec<_>>();
+        dirs.sort_by_key(|(_, c)| std::cmp::Reverse(c.0));
+        println!("\nTop directories by recently written bytes recursively in them");
+        for (p, c) in dirs.iter().take(self.limit) {
+            println!("{} {:8} {}", greek(c.0 as f64), c.1, p.display());
+        }
+
+        let mut users = self.users.iter().collect::<Vec<_>>();
+        users.sort_by_key(|(_, c)| std::cmp::Reverse(c.0));
+        println!("\nTop users by recently written bytes");
+        for (u, c) in users.iter().take(self.limit) {
+            println!("{} {:8} {}", greek(c.0 as f64), c.1, u);
+        }
+
+        println!("\nLargest recently written files");
+        for tp in to_sort_vec(&self.largest_file) {
+            println!("{} {}  age:[{}]", greek(tp.size as f64), tp.track.display(), get_age(now, tp.old));
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use std::{collections::HashMap, path::Path};
+
+use anyhow::{anyhow, Result};
+
+use crate::{
+    data::{greek, FileInfo},
+    pattern::{read_rules, PathPattern},
+};
+
+#[derive(Default, Clone, Copy)]
+struct Count {
+    files: u64,
+    bytes: u64,
+}
+
+impl Count {
+    fn add(&mut self, size: u64) {
+        self.files += 1;
+        self.bytes += size;
+    }
+}
+
+struct Rule {
+    pattern: PathPattern,
+    max_age: humantime::Duration,
+    age_text: String,
+    matched: Count,
+    violating: Count,
+}
+
+/// Retention rules of `<path pattern> <max age>` lines, the first rule matching a file applies to it.
+pub struct Retention {
+    now_ms: u64,
+    rules: Vec<Rule>,
+    owners: HashMap<String, Count>,
+}
+
+impl Retention {
+    pub fn load(path: &Path, now_ms: u64) -> Result<Self> {
+        let mut rules = vec![];
+        for (line, pattern, age) in read_rules(path)? {
+            let max_age = age
+                .parse::<humantime::Duration>()
+                .map_err(|e| anyhow!("{}:{} bad max age \"{}\": {}", path.display(), line, age, e))?;
+            rules.push(Rule {
+                pattern,
+                max_age,
+                age_text: age,
+                matched: Count::default(),
+                violating: Count::default(),
+            });
+        }
+        Ok(Retention {
+            now_ms,
+            rules,
+            owners: HashMap::new(),
+        })
+    }
+
+    pub fn record(&mut self, fi: &FileInfo) {
+        if let Some(rule) = self.rules.iter_mut().find(|r| r.pattern.matches(&fi.path)) {
+            rule.matched.add(fi.stat.size);
+            if self.now_ms.saturating_sub(fi.stat.mod_time) > rule.max_age.as_millis() as u64 {
+                rule.violating.add(fi.stat.size);
+                let o = match self.owners.get_mut(&fi.user) {
+                    Some(o) => o,
+                    None => self.owners.entry(fi.user.clone()).or_default(),
+                };
+                o.add(fi.stat.size);
+            }
+        }
+    }
+
+    pub fn report(&self, top_n: usize) {
+        let total = self.rules.iter().fold(Count::default(), |mut t, r| {
+            t.files += r.violating.files;
+            t.bytes += r.violating.bytes;
+            t
+        });
+        println!("\nRetention - {} reclaimable in {} files past their rule's max age", greek(total.bytes as f64), total.files);
+        println!("{:>9} {:>8} {:>9} {:>8} {:>10} pattern", "violating", "files", "matched", "files", "max age");
+        for r in &self.rules {
+            println!(
+                "{} {:8} {} {:8} {:>10} {}",
+                greek(r.violating.bytes as f64),
+                r.violating.files,
+                greek(r.matched.bytes as f64),
+                r.matched.files,
+                r.age_text,
+                r.pattern.text
+            );
+        }
+
+        let mut owners = self.owners.iter().collect::<Vec<_>>();
+        owners.sort_by_key(|(_, c)| std::cmp::Reverse(c.bytes));
+        println!("\nTop owners of data past retention");
+        for (o, c) in owners.iter().take(top_n) {
+            println!("{} {:8} {}", greek(c.bytes as f64), c.files, o);
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use crate::data::{greek, DirStat};
+
+/// files left once the small files directly in a directory are compacted into `target` sized files
+fn compacted_cnt(stat: &DirStat, target: u64) -> u64 {
+    let d = &stat.direct;
+    // compaction never leaves more files than it started with
+    let merged = if d.small_cnt > 0 { d.small_size.div_ceil(target.max(1)).clamp(1, d.small_cnt) } else { 0 };
+    d.entry_cnt - d.small_cnt + merged
+}
+
+/// Directories ranked by how many small files sit directly in them.
+pub fn report(dtree: &HashMap<PathBuf, DirStat>, small_size: u64, target: u64, top_n: usize) {
+    let mut dirs = dtree.iter().filter(|(_, s)| s.direct.small_cnt > 0).collect::<Vec<_>>();
+    dirs.sort_by_key(|(_, s)| std::cmp::Reverse(s.direct.small_cnt));
+
+    let (mut small, mut before, mut after) = (0u64, 0u64, 0u64);
+    for (_, s) in &dirs {
+        small += s.direct.small_cnt;
+        before += s.direct.entry_cnt;
+        after += compacted_cnt(s, target);
+    }
+
+    println!(
+        "\nSmall file hotspots - files below {} directly in a directory, compacted to {}",
+        greek(small_size as f64),
+        greek(target as f64)
+    );
+    println!(
+        "{} small files in {} directories, compaction would take {} entries down to {} (-{})",
+        small,
+        dirs.len(),
+        before,
+        after,
+        before.saturating_sub(after)
+    );
+    println!("{:>8} {:>8} {:>9} {:>8} directory", "small", "entries", "avg size", "after");
+    for (p, s) in dirs.iter().take(top_n) {
+        let avg = s.direct.size.checked_div(s.direct.entry_cnt).unwrap_or(0);
+        println!(
+            "{:8} {:8} {:>9} {:8} {}",
+            s.direct.small_cnt,
+            s.direct.entry_cnt,
+            greek(avg as f64),
+            compacted_cnt(s, target),
+            p.display()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dir(entries: u64, small_cnt: u64, small_size: u64) -> DirStat {
+        let mut s = DirStat::empty();
+        s.direct.entry_cnt = entries;
+        s.direct.small_cnt = small_cnt;
+        s.direct.small_size = small_size;
+        s
+    }
+
+    #[test]
+    fn compaction_never_adds_files() {
+        // 10 files of 10MB go into one 128MB file
+        assert_eq!(compacted_cnt(&dir(12, 10, 100 << 20), 128 << 20), 3);
+        // one 200MB small file against a 128MB target stays one file
+        assert_eq!(compacted_cnt(&dir(1, 1, 200 << 20), 128 << 20), 1);
+        assert_eq!(compacted_cnt(&dir(4, 0, 0), 128 << 20), 4);
+    }
+}
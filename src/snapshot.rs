@@ -0,0 +1,77 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    cli::SnapshotMode,
+    data::{greek, FileInfo},
+    pattern::path_names,
+};
+
+/// Entries under a .snapshot directory, kept out of the main totals unless snapshots are included.
+/// Separately they roll up per snapshottable directory and snapshot name.
+pub struct Snapshots {
+    pub mode: SnapshotMode,
+    skipped: u64,
+    /// bytes and files per (snapshottable directory, snapshot)
+    snaps: HashMap<(PathBuf, String), (u64, u64)>,
+}
+
+/// the snapshottable directory and snapshot name of a path inside a snapshot
+pub fn snapshot_of(path: &Path) -> Option<(PathBuf, &str)> {
+    let names = path_names(path);
+    let i = names.iter().position(|n| *n == ".snapshot")?;
+    let mut dir = PathBuf::from("/");
+    dir.extend(&names[..i]);
+    Some((dir, names.get(i + 1).copied().unwrap_or("")))
+}
+
+impl Snapshots {
+    pub fn new(mode: SnapshotMode) -> Self {
+        Snapshots {
+            mode,
+            skipped: 0,
+            snaps: HashMap::new(),
+        }
+    }
+
+    /// true when the entry is in a snapshot and has been taken out of the main totals
+    pub fn take(&mut self, fi: &FileInfo) -> bool {
+        if self.mode == SnapshotMode::Include {
+            return false;
+        }
+        let (dir, name) = match snapshot_of(&fi.path) {
+            Some(s) => s,
+            None => return false,
+        };
+        self.skipped += 1;
+        if self.mode == SnapshotMode::Separate && !fi.is_dir() {
+            let s = self.snaps.entry((dir, name.to_string())).or_insert((0, 0));
+            s.0 += fi.stat.size;
+            s.1 += 1;
+        }
+        true
+    }
+
+    pub fn report(&self, top_n: usize) {
+        match self.mode {
+            SnapshotMode::Include => {}
+            SnapshotMode::Exclude => println!("\nSnapshots - {} entries under .snapshot directories left out", self.skipped),
+            SnapshotMode::Separate => {
+                let mut snaps = self.snaps.iter().collect::<Vec<_>>();
+                snaps.sort_by_key(|(_, s)| std::cmp::Reverse(s.0));
+                let bytes = snaps.iter().map(|(_, s)| s.0).sum::<u64>();
+                println!(
+                    "\nSnapshots - {} in {} snapshots, {} entries kept apart from the totals above",
+                    greek(bytes as f64),
+                    snaps.len(),
+                    self.skipped
+                );
+                for ((dir, name), s) in snaps.iter().take(top_n) {
+                    println!("{} {:8} {} {}", greek(s.0 as f64), s.1, dir.display(), name);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use std::{collections::HashMap, path::PathBuf, time::SystemTime};
+
+use crate::data::{get_age, greek, DirStat};
+
+/// Directories whose newest file is older than `max_age_ms`, collapsed to the highest stale ancestor
+/// and ranked by recursive size.
+pub fn report(dtree: &HashMap<PathBuf, DirStat>, now_ms: u64, max_age_ms: u64, top_n: usize) {
+    let cutoff = now_ms.saturating_sub(max_age_ms);
+    // empty directories have no writes to go by
+    let is_stale = |s: &DirStat| s.recurse.entry_cnt > 0 && s.recurse.new < cutoff;
+
+    let mut stale = dtree
+        .iter()
+        .filter(|(_, s)| is_stale(s))
+        .filter(|(p, _)| match p.parent().and_then(|pp| dtree.get(pp)) {
+            Some(ps) => !is_stale(ps),
+            None => true,
+        })
+        .collect::<Vec<_>>();
+    stale.sort_by_key(|(_, s)| std::cmp::Reverse(s.recurse.size));
+
+    let (bytes, files) = stale.iter().fold((0, 0), |t, (_, s)| (t.0 + s.recurse.size, t.1 + s.recurse.entry_cnt));
+    let now = SystemTime::now();
+    println!(
+        "\nStale directories with no writes in {} - {} in {} entries under {} directories",
+        get_age(now, cutoff),
+        greek(bytes as f64),
+        files,
+        stale.len()
+    );
+    for (p, s) in stale.iter().take(top_n) {
+        println!(
+            "{} {:8} {}  newest:[{}]",
+            greek(s.recurse.size as f64),
+            s.recurse.entry_cnt,
+            p.display(),
+            get_age(now, s.recurse.new)
+        );
+    }
+}
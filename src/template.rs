@@ -0,0 +1,122 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    data::{greek, DirStat},
+    pattern::path_names,
+};
+
+fn is_uuid(s: &str) -> bool {
+    let groups = s.split('-').map(|g| g.len()).collect::<Vec<_>>();
+    groups == [8, 4, 4, 4, 12] && s.chars().all(|c| c == '-' || c.is_ascii_hexdigit())
+}
+
+fn digits(s: &str, len: usize) -> bool {
+    s.len() == len && s.bytes().all(|b| b.is_ascii_digit())
+}
+
+fn is_year(s: &str) -> bool {
+    digits(s, 4) && (s.starts_with("19") || s.starts_with("20"))
+}
+
+/// date shaped values: 2021, 2021-07-09, 20210709 and 2021-07
+fn date_template(s: &str) -> Option<&'static str> {
+    let parts = s.split('-').collect::<Vec<_>>();
+    match parts[..] {
+        [y] if is_year(y) => Some("{yyyy}"),
+        [y, m] if is_year(y) && digits(m, 2) => Some("{yyyy}-{mm}"),
+        [y, m, d] if is_year(y) && digits(m, 2) && digits(d, 2) => Some("{yyyy}-{mm}-{dd}"),
+        [ymd] if digits(ymd, 8) && is_year(&ymd[..4]) => Some("{yyyy}{mm}{dd}"),
+        _ => None,
+    }
+}
+
+/// every run of digits becomes {n}
+fn number_template(s: &str) -> String {
+    let mut t = String::new();
+    let mut in_num = false;
+    for c in s.chars() {
+        if c.is_ascii_digit() {
+            if !in_num {
+                t.push_str("{n}");
+            }
+            in_num = true;
+        } else {
+            t.push(c);
+            in_num = false;
+        }
+    }
+    t
+}
+
+/// One path component with its variable parts replaced, `prev` is the template of the component above it
+/// so 2021/07/09 reads as {yyyy}/{mm}/{dd}.
+pub fn component_template(name: &str, prev: &str) -> String {
+    if let Some((k, v)) = name.split_once('=') {
+        return format!("{}={}", k, component_template(v, ""));
+    }
+    if is_uuid(name) {
+        return "{uuid}".to_string();
+    }
+    if let Some(d) = date_template(name) {
+        return d.to_string();
+    }
+    if digits(name, 2) {
+        if prev == "{yyyy}" {
+            return "{mm}".to_string();
+        }
+        if prev == "{mm}" {
+            return "{dd}".to_string();
+        }
+    }
+    number_template(name)
+}
+
+/// the path with every component templated, e.g. /logs/{yyyy}/{mm}/{dd}/host{n}
+pub fn path_template(path: &Path) -> String {
+    let mut t = String::new();
+    let mut prev = String::new();
+    for name in path_names(path) {
+        prev = component_template(name, &prev);
+        t.push('/');
+        t.push_str(&prev);
+    }
+    if t.is_empty() {
+        t.push('/');
+    }
+    t
+}
+
+#[derive(Default)]
+struct Family {
+    dirs: u64,
+    entries: u64,
+    bytes: u64,
+    example: PathBuf,
+}
+
+/// Directories grouped by their path template with the stats directly in them summed,
+/// so thousands of dated or numbered siblings show up as one family.
+pub fn report(dtree: &HashMap<PathBuf, DirStat>, top_n: usize) {
+    let mut families: HashMap<String, Family> = HashMap::new();
+    for (p, s) in dtree {
+        let f = families.entry(path_template(p)).or_default();
+        if f.dirs == 0 || p < &f.example {
+            f.example = p.clone();
+        }
+        f.dirs += 1;
+        f.entries += s.direct.entry_cnt;
+        f.bytes += s.direct.size;
+    }
+    let mut families = families.into_iter().collect::<Vec<_>>();
+    families.sort_by_key(|(_, f)| std::cmp::Reverse(f.bytes));
+
+    let multi = families.iter().filter(|(_, f)| f.dirs > 1).count();
+    println!("\nPath templates - {} families, {} of them with more than one directory", families.len(), multi);
+    println!("{:>9} {:>8} {:>7} template (first instance)", "size", "entries", "dirs");
+    for (t, f) in families.iter().take(top_n) {
+        println!("{} {:8} {:7} {} ({})", greek(f.bytes as f64), f.entries, f.dirs, t, f.example.display());
+    }
+}
@@ -0,0 +1,102 @@
+use std::{
+    cmp::min,
+    collections::HashMap,
+    time::SystemTime,
+};
+
+use crate::{
+    data::{get_age, greek, oiv_time, FileInfo},
+    pattern::path_names,
+};
+
+#[derive(Default)]
+struct UserTrash {
+    current: (u64, u64),
+    /// time, bytes and files per checkpoint name
+    checkpoints: HashMap<String, (u64, u64, u64)>,
+}
+
+/// Bytes in the .Trash of every home, Current apart from the timestamped checkpoints the trash
+/// emptier removes once they are older than the trash interval.
+pub struct Trash {
+    now_ms: u64,
+    interval_ms: u64,
+    tz_offset: i64,
+    users: HashMap<String, UserTrash>,
+}
+
+/// checkpoint names are yyMMddHHmm or yyMMddHHmmss
+fn checkpoint_time(name: &str, tz_offset: i64) -> Option<u64> {
+    if !(name.len() == 10 || name.len() == 12) || !name.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let t = format!("20{}-{}-{} {}:{}", &name[0..2], &name[2..4], &name[4..6], &name[6..8], &name[8..10]);
+    oiv_time(&t, tz_offset).ok()
+}
+
+impl Trash {
+    pub fn new(now_ms: u64, interval_ms: u64, tz_offset: i64) -> Self {
+        Trash {
+            now_ms,
+            interval_ms,
+            tz_offset,
+            users: HashMap::new(),
+        }
+    }
+
+    pub fn record(&mut self, fi: &FileInfo) {
+        let names = path_names(&fi.path);
+        let i = match names.iter().position(|n| *n == ".Trash") {
+            Some(i) if i > 0 && i + 2 < names.len() => i,
+            _ => return,
+        };
+        let t = self.users.entry(names[i - 1].to_string()).or_default();
+        if names[i + 1] == "Current" {
+            t.current.0 += fi.stat.size;
+            t.current.1 += 1;
+            return;
+        }
+        // a checkpoint not named like one is dated by its oldest file
+        let at = checkpoint_time(names[i + 1], self.tz_offset).unwrap_or(fi.stat.mod_time);
+        let c = t.checkpoints.entry(names[i + 1].to_string()).or_insert((at, 0, 0));
+        c.0 = min(c.0, at);
+        c.1 += fi.stat.size;
+        c.2 += 1;
+    }
+
+    pub fn report(&self, top_n: usize) {
+        let now = SystemTime::now();
+        let expire_before = self.now_ms.saturating_sub(self.interval_ms);
+        let mut users = self
+            .users
+            .iter()
+            .map(|(u, t)| {
+                let cp = t.checkpoints.values().fold((0, 0), |s, c| (s.0 + c.1, s.1 + c.2));
+                let expiring = t.checkpoints.values().filter(|c| c.0 < expire_before).map(|c| c.1).sum::<u64>();
+                let oldest = t.checkpoints.values().map(|c| c.0).min();
+                (u, t, cp, expiring, oldest)
+            })
+            .collect::<Vec<_>>();
+        users.sort_by_key(|(_, t, cp, _, _)| std::cmp::Reverse(t.current.0 + cp.0));
+
+        let total = users.iter().fold((0, 0), |s, (_, t, cp, e, _)| (s.0 + t.current.0 + cp.0, s.1 + e));
+        println!(
+            "\nTrash - {} in user trash, {} in checkpoints older than the {} interval expire at the next checkpoint",
+            greek(total.0 as f64),
+            greek(total.1 as f64),
+            humantime::format_duration(std::time::Duration::from_millis(self.interval_ms))
+        );
+        println!("{:>9} {:>9} {:>9} {:>5} {:>9} user", "current", "ckpts", "expiring", "count", "oldest");
+        for (u, t, cp, expiring, oldest) in users.iter().take(top_n) {
+            println!(
+                "{} {} {} {:5} {:>9} {}",
+                greek(t.current.0 as f64),
+                greek(cp.0 as f64),
+                greek(*expiring as f64),
+                t.checkpoints.len(),
+                oldest.map_or("-".to_string(), |o| get_age(now, min(o, self.now_ms))),
+                u
+            );
+        }
+    }
+}
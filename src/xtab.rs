@@ -0,0 +1,72 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use crate::data::{dir_at_depth, greek, pct, FileInfo};
+
+/// Size and count of every directory at a fixed depth broken down by the owners of the files under it.
+pub struct OwnerXtab {
+    depth: usize,
+    cells: HashMap<PathBuf, HashMap<String, (u64, u64)>>,
+}
+
+fn sort_by_size<K>(v: &mut [(K, (u64, u64))]) {
+    v.sort_by_key(|e| std::cmp::Reverse(e.1 .0));
+}
+
+impl OwnerXtab {
+    pub fn new(depth: usize) -> Self {
+        OwnerXtab {
+            depth,
+            cells: HashMap::new(),
+        }
+    }
+
+    pub fn record(&mut self, fi: &FileInfo) {
+        if let Some(dir) = dir_at_depth(&fi.path, self.depth) {
+            let cell = self.cells.entry(dir).or_default().entry(fi.user.clone()).or_insert((0, 0));
+            cell.0 += fi.stat.size;
+            cell.1 += 1;
+        }
+    }
+
+    pub fn report(&self, top_n: usize) {
+        let mut dirs = self
+            .cells
+            .iter()
+            .map(|(d, owners)| (d, owners.values().fold((0, 0), |t, c| (t.0 + c.0, t.1 + c.1))))
+            .collect::<Vec<_>>();
+        sort_by_size(&mut dirs);
+
+        println!("\nTop owners per directory at depth {}", self.depth);
+        for (d, total) in dirs.iter().take(top_n) {
+            println!("{} {:8} {}", greek(total.0 as f64), total.1, d.display());
+            let mut owners = self.cells[*d].iter().map(|(o, c)| (o, *c)).collect::<Vec<_>>();
+            sort_by_size(&mut owners);
+            for (o, c) in owners.iter().take(top_n) {
+                println!("    {} {:8} {:5.1}% {}", greek(c.0 as f64), c.1, pct(c.0, total.0), o);
+            }
+        }
+
+        type OwnerDirs<'a> = HashMap<&'a String, Vec<(&'a PathBuf, (u64, u64))>>;
+        let mut by_owner: OwnerDirs = HashMap::new();
+        for (d, owners) in &self.cells {
+            for (o, c) in owners {
+                by_owner.entry(o).or_default().push((d, *c));
+            }
+        }
+        let mut owners = by_owner
+            .iter()
+            .map(|(o, v)| (*o, v.iter().fold((0, 0), |t, (_, c)| (t.0 + c.0, t.1 + c.1))))
+            .collect::<Vec<_>>();
+        sort_by_size(&mut owners);
+
+        println!("\nTop directories at depth {} per owner", self.depth);
+        for (o, total) in owners.iter().take(top_n) {
+            println!("{} {:8} {}", greek(total.0 as f64), total.1, o);
+            let mut dirs = by_owner[o].clone();
+            sort_by_size(&mut dirs);
+            for (d, c) in dirs.iter().take(top_n) {
+                println!("    {} {:8} {:5.1}% {}", greek(c.0 as f64), c.1, pct(c.0, total.0), d.display());
+            }
+        }
+    }
+}